@@ -0,0 +1,23 @@
+//! `SynapseFile::from_bytes` on arbitrary blobs.
+
+#![no_main]
+
+use libfuzzer_sys::fuzz_target;
+use wid::SynapseFile;
+
+fuzz_target!(|data: &[u8]| {
+    // Safety: any blob may be rejected, but never by panicking.
+    let Ok(mut file) = SynapseFile::from_bytes(data) else {
+        return;
+    };
+
+    // Correctness: an accepted blob re-encodes to one that decodes to the
+    // same manifest identity and payload. Re-encoding may legitimately fail
+    // (e.g. pretty-printing pushes the manifest over the size limit).
+    let Ok(bytes) = file.to_bytes() else {
+        return;
+    };
+    let again = SynapseFile::from_bytes(&bytes).expect("re-encoded blob must decode");
+    assert_eq!(again.manifest.id, file.manifest.id);
+    assert_eq!(again.payload, file.payload);
+});
@@ -0,0 +1,36 @@
+//! `parse_hlc_wid_with_unit` on arbitrary strings and parameters.
+//!
+//! Input layout matches `fuzz_parse_wid`: `w`, `z` and time-unit bytes, then
+//! the candidate HLC-WID.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use wid::{MAX_NODE_LEN, TimeUnit, parse_hlc_wid_with_unit, validate_node};
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    w: u8,
+    z: u8,
+    unit: u8,
+    wid: &'a str,
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let (w, z) = (usize::from(input.w), usize::from(input.z));
+    let unit = [TimeUnit::Sec, TimeUnit::Ms, TimeUnit::Ns][usize::from(input.unit % 3)];
+    // Safety: any input may be rejected, but never by panicking.
+    let Ok(parsed) = parse_hlc_wid_with_unit(input.wid, w, z, unit) else {
+        return;
+    };
+
+    // Correctness: an accepted string is an HLC-WID under these parameters.
+    assert_eq!(parsed.raw, input.wid);
+    assert!(parsed.logical_counter < 10_u64.pow(w as u32));
+    assert!(parsed.node.len() <= MAX_NODE_LEN);
+    assert!(validate_node(&parsed.node).is_ok());
+    if let Some(pad) = &parsed.padding {
+        assert_eq!(pad.len(), z);
+    }
+});
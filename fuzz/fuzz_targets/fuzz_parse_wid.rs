@@ -0,0 +1,38 @@
+//! `parse_wid_with_unit` on arbitrary strings and parameters.
+//!
+//! Input layout (what `Arbitrary` decodes, and how the seeds are written):
+//! one byte each for `w`, `z` and the time unit, then the candidate WID.
+
+#![no_main]
+
+use arbitrary::Arbitrary;
+use libfuzzer_sys::fuzz_target;
+use wid::{TimeUnit, max_wid_at, min_wid_at, parse_wid_with_unit};
+
+#[derive(Debug, Arbitrary)]
+struct Input<'a> {
+    w: u8,
+    z: u8,
+    unit: u8,
+    wid: &'a str,
+}
+
+fuzz_target!(|input: Input<'_>| {
+    let (w, z) = (usize::from(input.w), usize::from(input.z));
+    let unit = [TimeUnit::Sec, TimeUnit::Ms, TimeUnit::Ns][usize::from(input.unit % 3)];
+    // Safety: any input may be rejected, but never by panicking.
+    let Ok(parsed) = parse_wid_with_unit(input.wid, w, z, unit) else {
+        return;
+    };
+
+    // Correctness: an accepted string is a WID under these parameters.
+    assert_eq!(parsed.raw, input.wid);
+    assert!(parsed.sequence < 10_u64.pow(w as u32));
+    let z_seen = parsed.padding.as_ref().map_or(0, |pad| {
+        assert_eq!(pad.len(), z);
+        z
+    });
+    let lo = min_wid_at(parsed.timestamp, w, z_seen, unit);
+    let hi = max_wid_at(parsed.timestamp, w, z_seen, unit);
+    assert!(lo.as_str() <= input.wid && input.wid <= hi.as_str());
+});
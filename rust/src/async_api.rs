@@ -1,6 +1,120 @@
 //! Async convenience API built on top of sync generators.
 
-use crate::{HLCWidGen, TimeUnit, WidError, WidGen};
+use std::pin::Pin;
+use std::sync::Mutex;
+use std::task::{Context, Poll};
+
+use futures_core::Stream;
+
+use crate::{Clock, HLCWidGen, TimeUnit, WidError, WidGen};
+
+/// A `Stream` of WIDs, ending after `count` items or never when `count` is
+/// `None`.
+///
+/// IDs are generated on demand, so an infinite stream holds no backlog and
+/// works with `tokio-stream`/`futures` adapters (`throttle`, `take`, ...).
+/// Every poll is immediately ready; pace consumption with such an adapter.
+pub struct WidStream {
+    generator: Mutex<WidGen>,
+    count: Option<usize>,
+}
+
+impl WidStream {
+    pub fn new(generator: WidGen, count: Option<usize>) -> Self {
+        Self {
+            generator: Mutex::new(generator),
+            count,
+        }
+    }
+}
+
+impl Stream for WidStream {
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if !take_one(&mut self.count) {
+            return Poll::Ready(None);
+        }
+        let mut generator = self.generator.lock().unwrap_or_else(|e| e.into_inner());
+        Poll::Ready(Some(generator.next_wid()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.count.map_or((usize::MAX, None), |n| (n, Some(n)))
+    }
+}
+
+/// The HLC-WID counterpart of `WidStream`.
+pub struct HLCStream {
+    generator: Mutex<HLCWidGen>,
+    count: Option<usize>,
+}
+
+impl HLCStream {
+    pub fn new(generator: HLCWidGen, count: Option<usize>) -> Self {
+        Self {
+            generator: Mutex::new(generator),
+            count,
+        }
+    }
+}
+
+impl Stream for HLCStream {
+    type Item = String;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<Self::Item>> {
+        if !take_one(&mut self.count) {
+            return Poll::Ready(None);
+        }
+        let mut generator = self.generator.lock().unwrap_or_else(|e| e.into_inner());
+        Poll::Ready(Some(generator.next_hlc_wid()))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.count.map_or((usize::MAX, None), |n| (n, Some(n)))
+    }
+}
+
+/// Consume one item of a bounded stream's budget; `false` once it is spent.
+fn take_one(count: &mut Option<usize>) -> bool {
+    match count {
+        Some(0) => false,
+        Some(n) => {
+            *n -= 1;
+            true
+        }
+        None => true,
+    }
+}
+
+/// Drain a finite stream into a vector.
+async fn collect<S: Stream + Unpin>(mut stream: S) -> Vec<S::Item> {
+    let mut out = Vec::with_capacity(stream.size_hint().0);
+    std::future::poll_fn(|cx| {
+        while let Poll::Ready(item) = Pin::new(&mut stream).poll_next(cx) {
+            match item {
+                Some(item) => out.push(item),
+                None => return Poll::Ready(()),
+            }
+        }
+        Poll::Pending
+    })
+    .await;
+    out
+}
+
+/// A `WidStream` whose generator reads ticks from `clock`, so tests (e.g.
+/// with a paused or mocked clock) see predictable timestamps.
+pub fn wid_stream_with_clock(
+    clock: Box<dyn Clock>,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    count: Option<usize>,
+) -> Result<WidStream, WidError> {
+    let generator = WidGen::with_clock(w, z, time_unit, clock)?;
+    Ok(WidStream::new(generator, count))
+}
 
 /// Get one WID in async contexts.
 pub async fn async_next_wid(w: usize, z: usize, time_unit: TimeUnit) -> Result<String, WidError> {
@@ -26,8 +140,8 @@ pub async fn async_wid_stream(
     z: usize,
     time_unit: TimeUnit,
 ) -> Result<Vec<String>, WidError> {
-    let mut generator = WidGen::new_with_time_unit(w, z, None, time_unit)?;
-    Ok((0..count).map(|_| generator.next_wid()).collect())
+    let generator = WidGen::new_with_time_unit(w, z, None, time_unit)?;
+    Ok(collect(WidStream::new(generator, Some(count))).await)
 }
 
 /// Generate a finite async stream of HLC-WIDs as a vector.
@@ -38,15 +152,17 @@ pub async fn async_hlc_wid_stream(
     z: usize,
     time_unit: TimeUnit,
 ) -> Result<Vec<String>, WidError> {
-    let mut generator = HLCWidGen::new_with_time_unit(node.to_string(), w, z, time_unit)?;
-    Ok((0..count).map(|_| generator.next_hlc_wid()).collect())
+    let generator = HLCWidGen::new_with_time_unit(node.to_string(), w, z, time_unit)?;
+    Ok(collect(HLCStream::new(generator, Some(count))).await)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::{validate_hlc_wid_with_unit, validate_wid_with_unit};
+    use futures::StreamExt;
     use futures::executor::block_on;
+    use std::sync::atomic::{AtomicI64, Ordering};
 
     #[test]
     fn async_next_wid_ms_is_valid() {
@@ -74,4 +190,50 @@ mod tests {
         assert_eq!(values.len(), 2);
         assert!(values.iter().all(|v| v.contains("-node01")));
     }
+
+    #[test]
+    fn wid_stream_is_lazy_and_bounded() {
+        let generator = WidGen::new(4, 0, None).unwrap();
+        let values: Vec<String> = block_on(WidStream::new(generator, Some(3)).collect());
+        assert_eq!(values.len(), 3);
+        assert!(values.windows(2).all(|pair| pair[0] < pair[1]));
+
+        // Unbounded: adapters decide how much to pull.
+        let generator = HLCWidGen::new("node01".to_string(), 4, 0).unwrap();
+        let stream = HLCStream::new(generator, None);
+        assert_eq!(stream.size_hint(), (usize::MAX, None));
+        let values: Vec<String> = block_on(stream.take(5).collect());
+        assert_eq!(values.len(), 5);
+        assert!(values.iter().all(|v| v.ends_with("-node01")));
+    }
+
+    #[test]
+    fn wid_stream_with_clock_is_deterministic() {
+        /// Moves one tick forward every time it is read, i.e. once per poll.
+        struct SteppingClock(AtomicI64);
+        impl Clock for SteppingClock {
+            fn now_tick(&self) -> i64 {
+                self.0.fetch_add(1, Ordering::SeqCst)
+            }
+        }
+
+        let run = || {
+            let clock = Box::new(SteppingClock(AtomicI64::new(1_770_887_730)));
+            let stream = wid_stream_with_clock(clock, 4, 0, TimeUnit::Sec, Some(3)).unwrap();
+            block_on(stream.collect::<Vec<String>>())
+        };
+        let values = run();
+        assert_eq!(
+            values,
+            [
+                "20260212T091530.0000Z",
+                "20260212T091531.0000Z",
+                "20260212T091532.0000Z"
+            ]
+        );
+        assert_eq!(run(), values);
+
+        let clock = Box::new(SteppingClock(AtomicI64::new(0)));
+        assert!(wid_stream_with_clock(clock, 0, 0, TimeUnit::Sec, None).is_err());
+    }
 }
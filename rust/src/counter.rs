@@ -0,0 +1,215 @@
+//! Counter-based IDs for devices without a usable clock.
+//!
+//! Format: `C<counter12>.<seqW>Z-<node>`
+
+use chrono::{DateTime, Utc};
+
+use crate::hlc::validate_node;
+use crate::wid::{MAX_TICK_SEC, TimeUnit, WidError, check_generator_w, format_tick};
+
+const COUNTER_DIGITS: usize = 12;
+const MAX_COUNTER: u64 = 10_u64.pow(COUNTER_DIGITS as u32) - 1;
+
+/// Generator for clockless IDs ordered by a persisted counter.
+///
+/// The counter stands in for the timestamp: it only moves forward, either
+/// when the sequence overflows or when the device calls `advance` (e.g. once
+/// per boot). The counter is zero-padded so IDs sort lexicographically.
+///
+/// # Merge semantics
+///
+/// Once a clock is available, `rebase` maps counter `c` to the second
+/// `base_time + c` and yields an HLC-WID (`Z=0`) with the same sequence and
+/// node. Order within a device is preserved. Pick `base_time` so that
+/// `base_time + last_counter` is not after the first clocked ID the device
+/// emits; rebased IDs then sort before it. Across devices, rebased IDs
+/// interleave by counter, with the node segment breaking ties.
+pub struct CounterWidGen {
+    w: usize,
+    node: String,
+    max_seq: i64,
+    counter: u64,
+    seq: i64,
+}
+
+impl CounterWidGen {
+    /// Create a generator resuming at `counter`.
+    ///
+    /// Pass the last persisted counter plus one after a restart so new IDs
+    /// sort after everything emitted before it.
+    pub fn new(node: String, w: usize, counter: u64) -> Result<Self, WidError> {
+        check_generator_w(w)?;
+        validate_node(&node)?;
+        if counter > MAX_COUNTER {
+            return Err(WidError::InvalidTimestamp);
+        }
+        Ok(Self {
+            w,
+            node,
+            max_seq: 10_i64.pow(w as u32) - 1,
+            counter,
+            seq: -1,
+        })
+    }
+
+    /// Generate the next counter ID.
+    ///
+    /// # Panics
+    ///
+    /// Panics once the counter would pass `999999999999`; use
+    /// `try_next_counter_wid` to handle that as an error.
+    pub fn next_counter_wid(&mut self) -> String {
+        self.try_next_counter_wid()
+            .expect("counter exhausted its 12 digits")
+    }
+
+    /// Like `next_counter_wid`, but fails with `InvalidTimestamp` instead of
+    /// panicking when the sequence would roll the counter past its 12 digits.
+    /// The state is left untouched on error.
+    pub fn try_next_counter_wid(&mut self) -> Result<String, WidError> {
+        if self.seq >= self.max_seq {
+            if self.counter >= MAX_COUNTER {
+                return Err(WidError::InvalidTimestamp);
+            }
+            self.counter += 1;
+            self.seq = 0;
+        } else {
+            self.seq += 1;
+        }
+        Ok(format!(
+            "C{:0cw$}.{:0w$}Z-{}",
+            self.counter,
+            self.seq,
+            self.node,
+            cw = COUNTER_DIGITS,
+            w = self.w
+        ))
+    }
+
+    /// Generate n counter IDs, or fewer if the counter runs out.
+    pub fn next_n(&mut self, n: usize) -> Vec<String> {
+        self.take(n).collect()
+    }
+
+    /// Move to the next counter value, restarting the sequence. Fails with
+    /// `InvalidTimestamp`, leaving the state untouched, at the last counter.
+    pub fn advance(&mut self) -> Result<(), WidError> {
+        if self.counter >= MAX_COUNTER {
+            return Err(WidError::InvalidTimestamp);
+        }
+        self.counter += 1;
+        self.seq = -1;
+        Ok(())
+    }
+
+    /// Current `(counter, last_seq)`; persist the counter across restarts.
+    pub fn state(&self) -> (u64, i64) {
+        (self.counter, self.seq)
+    }
+
+    /// Convert a counter ID from this generator's config into an HLC-WID.
+    ///
+    /// See the type docs for how `base_time` should be chosen.
+    pub fn rebase(&self, id: &str, base_time: DateTime<Utc>) -> Result<String, WidError> {
+        let invalid = || WidError::InvalidFormat(id.to_string());
+        let (counter, rest) = id
+            .strip_prefix('C')
+            .and_then(|rest| rest.split_once('.'))
+            .ok_or_else(invalid)?;
+        let (seq, node) = rest.split_once("Z-").ok_or_else(invalid)?;
+        if counter.len() != COUNTER_DIGITS
+            || seq.len() != self.w
+            || !counter
+                .bytes()
+                .chain(seq.bytes())
+                .all(|b| b.is_ascii_digit())
+        {
+            return Err(invalid());
+        }
+        validate_node(node)?;
+
+        let counter: i64 = counter.parse().map_err(|_| invalid())?;
+        let tick = base_time.timestamp() + counter;
+        if !(0..=MAX_TICK_SEC).contains(&tick) {
+            return Err(WidError::InvalidTimestamp);
+        }
+        Ok(format!(
+            "{}.{seq}Z-{node}",
+            format_tick(TimeUnit::Sec, tick)
+        ))
+    }
+}
+
+impl Iterator for CounterWidGen {
+    type Item = String;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        self.try_next_counter_wid().ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{parse_hlc_wid, validate_hlc_wid};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_counter_ids_are_monotone() {
+        let mut counter_gen = CounterWidGen::new("dev1".to_string(), 1, 7).unwrap();
+        let mut ids: Vec<String> = counter_gen.by_ref().take(15).collect();
+        counter_gen.advance().unwrap();
+        ids.push(counter_gen.next_counter_wid());
+
+        assert_eq!(ids[0], "C000000000007.0Z-dev1");
+        assert_eq!(ids[10], "C000000000008.0Z-dev1");
+        assert_eq!(ids[15], "C000000000009.0Z-dev1");
+        assert!(ids.windows(2).all(|p| p[0] < p[1]));
+        assert_eq!(counter_gen.state(), (9, 0));
+    }
+
+    #[test]
+    fn test_rebase_yields_valid_hlc_wids() {
+        let mut counter_gen = CounterWidGen::new("dev1".to_string(), 4, 0).unwrap();
+        let base = Utc.with_ymd_and_hms(2026, 2, 12, 9, 15, 30).unwrap();
+        let ids = counter_gen.next_n(3);
+        counter_gen.advance().unwrap();
+        let later = counter_gen.next_counter_wid();
+
+        let rebased: Vec<String> = ids
+            .iter()
+            .chain([&later])
+            .map(|id| counter_gen.rebase(id, base).unwrap())
+            .collect();
+        assert_eq!(rebased[0], "20260212T091530.0000Z-dev1");
+        assert_eq!(rebased[3], "20260212T091531.0000Z-dev1");
+        assert!(rebased.iter().all(|id| validate_hlc_wid(id, 4, 0)));
+        assert!(rebased.windows(2).all(|p| p[0] < p[1]));
+        assert_eq!(parse_hlc_wid(&rebased[2], 4, 0).unwrap().logical_counter, 2);
+
+        assert!(counter_gen.rebase("C0.0000Z-dev1", base).is_err());
+        assert!(
+            counter_gen
+                .rebase("20260212T091530.0000Z-dev1", base)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_counter_stops_at_twelve_digits() {
+        let mut counter_gen = CounterWidGen::new("dev1".to_string(), 1, MAX_COUNTER).unwrap();
+        let ids = counter_gen.next_n(15);
+        assert_eq!(ids.len(), 10);
+        assert_eq!(ids[9], "C999999999999.9Z-dev1");
+        assert!(matches!(
+            counter_gen.try_next_counter_wid(),
+            Err(WidError::InvalidTimestamp)
+        ));
+        assert!(matches!(
+            counter_gen.advance(),
+            Err(WidError::InvalidTimestamp)
+        ));
+        assert_eq!(counter_gen.state(), (MAX_COUNTER, 9));
+    }
+}
@@ -0,0 +1,102 @@
+//! Length-prefixed binary frames of WIDs for IPC.
+//!
+//! ```text
+//! FRAME ::= COUNT:u32be { LEN:u32be ID-BYTES }*COUNT
+//! ```
+//!
+//! IDs are written verbatim, so any WID, HLC-WID or counter ID round-trips.
+//! For IDs bundled with payload data use the SYNAPSE manifest container.
+
+use crate::wid::WidError;
+
+/// Pack `ids` into one frame.
+pub fn pack_frame(ids: &[&str]) -> Vec<u8> {
+    let body: usize = ids.iter().map(|id| 4 + id.len()).sum();
+    let mut out = Vec::with_capacity(4 + body);
+    out.extend_from_slice(&(ids.len() as u32).to_be_bytes());
+    for id in ids {
+        out.extend_from_slice(&(id.len() as u32).to_be_bytes());
+        out.extend_from_slice(id.as_bytes());
+    }
+    out
+}
+
+/// Unpack a frame written by `pack_frame`.
+///
+/// Fails with `TruncatedFrame` when the data ends before the declared count
+/// or an ID's declared length, and with `InvalidFormat` for trailing bytes or
+/// IDs that are not UTF-8.
+pub fn unpack_frame(data: &[u8]) -> Result<Vec<String>, WidError> {
+    let mut rest = data;
+    let count = take_u32(&mut rest)? as usize;
+    // Each ID needs at least its 4-byte length, which bounds the allocation
+    // for a corrupt count.
+    let mut ids = Vec::with_capacity(count.min(rest.len() / 4));
+    for _ in 0..count {
+        let len = take_u32(&mut rest)? as usize;
+        if rest.len() < len {
+            return Err(WidError::TruncatedFrame);
+        }
+        let (id, tail) = rest.split_at(len);
+        let id = std::str::from_utf8(id)
+            .map_err(|_| WidError::InvalidFormat("frame entry is not UTF-8".to_string()))?;
+        ids.push(id.to_string());
+        rest = tail;
+    }
+    if !rest.is_empty() {
+        return Err(WidError::InvalidFormat(format!(
+            "{} trailing bytes after frame",
+            rest.len()
+        )));
+    }
+    Ok(ids)
+}
+
+fn take_u32(rest: &mut &[u8]) -> Result<u32, WidError> {
+    let Some((head, tail)) = rest.split_first_chunk::<4>() else {
+        return Err(WidError::TruncatedFrame);
+    };
+    *rest = tail;
+    Ok(u32::from_be_bytes(*head))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_frame_round_trip() {
+        let ids = [
+            "20260212T091530.0000Z-a3f91c",
+            "20260212T091530.0001Z-node01",
+            "",
+        ];
+        let frame = pack_frame(&ids);
+        assert_eq!(&frame[..4], &3u32.to_be_bytes());
+        assert_eq!(unpack_frame(&frame).unwrap(), ids);
+        assert!(unpack_frame(&pack_frame(&[])).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_truncated_frame() {
+        let frame = pack_frame(&["20260212T091530.0000Z", "20260212T091530.0001Z"]);
+        for cut in [0, 3, 4, 7, 10, frame.len() - 1] {
+            assert!(
+                matches!(unpack_frame(&frame[..cut]), Err(WidError::TruncatedFrame)),
+                "cut at {cut}"
+            );
+        }
+        // A huge declared count fails cleanly instead of allocating.
+        assert!(matches!(
+            unpack_frame(&u32::MAX.to_be_bytes()),
+            Err(WidError::TruncatedFrame)
+        ));
+
+        let mut trailing = frame.clone();
+        trailing.push(0);
+        assert!(matches!(
+            unpack_frame(&trailing),
+            Err(WidError::InvalidFormat(_))
+        ));
+    }
+}
@@ -2,69 +2,202 @@
 //!
 //! Format: `YYYYMMDDTHHMMSS[mmm].<lcW>Z-<node>[-<padZ>]`
 
-use chrono::{DateTime, TimeZone, Timelike, Utc};
+use chrono::{DateTime, Utc};
 use once_cell::sync::Lazy;
 use rand::random_range;
 use regex::Regex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use sha2::{Digest, Sha256};
+use std::cmp::Ordering;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
 
-use crate::wid::{TimeUnit, WidError};
+use crate::wid::{Clock, SystemClock, TimeUnit, WidError, format_tick, parse_timestamp};
 
 /// Parsed HLC-WID components.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Ordered causally: by `timestamp`, then `logical_counter`, then `node`.
+/// `padding` and `raw` break any remaining tie so `Ord` agrees with `Eq`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParsedHlcWid {
     pub raw: String,
     pub timestamp: DateTime<Utc>,
-    pub logical_counter: u32,
+    pub logical_counter: u64,
     pub node: String,
     pub padding: Option<String>,
 }
 
+impl ParsedHlcWid {
+    /// Wall-clock age of the ID; see `ParsedWid::elapsed`.
+    pub fn elapsed(&self) -> chrono::Duration {
+        Utc::now() - self.timestamp
+    }
+
+    /// `elapsed` in whole seconds, truncated toward zero.
+    pub fn age_secs(&self) -> i64 {
+        self.elapsed().num_seconds()
+    }
+
+    /// Whether the ID is more than `secs` seconds old. Future-dated IDs never
+    /// are.
+    pub fn is_older_than(&self, secs: u64) -> bool {
+        crate::wid::is_older_than(self.elapsed(), secs)
+    }
+}
+
+impl Ord for ParsedHlcWid {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (
+            self.timestamp,
+            self.logical_counter,
+            &self.node,
+            &self.padding,
+            &self.raw,
+        )
+            .cmp(&(
+                other.timestamp,
+                other.logical_counter,
+                &other.node,
+                &other.padding,
+                &other.raw,
+            ))
+    }
+}
+
+impl PartialOrd for ParsedHlcWid {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Validate an HLC-WID and require its timestamp to fall in the window; see
+/// `validate_wid_in_window`.
+pub fn validate_hlc_wid_in_window(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    max_age: Duration,
+    max_future: Duration,
+) -> bool {
+    parse_hlc_wid_with_unit(wid, w, z, time_unit)
+        .is_ok_and(|parsed| crate::wid::in_window(parsed.timestamp, max_age, max_future))
+}
+
+/// Upper bound on the node segment of an HLC-WID, in bytes.
+///
+/// Generators reject longer nodes so everything they emit parses with the
+/// default parsers; `parse_hlc_wid_with_max_node` accepts longer nodes from
+/// other sources.
+pub const MAX_NODE_LEN: usize = 64;
+
+/// Largest `max_node_len` that `parse_hlc_wid_with_max_node` accepts; keeps
+/// the compiled pattern well inside the regex size limit.
+pub const MAX_NODE_LEN_LIMIT: usize = 4096;
+
 static HLC_PATTERN_W4_Z0_SEC: Lazy<Regex> =
-    Lazy::new(|| Regex::new(r"^(\d{8})T(\d{6})\.(\d{4})Z-([A-Za-z0-9_]+)$").unwrap());
+    Lazy::new(|| Regex::new(r"^(\d{8})T(\d{6})\.(\d{4})Z-([A-Za-z0-9_]{1,64})$").unwrap());
 
-fn build_pattern(w: usize, z: usize, time_unit: TimeUnit) -> Regex {
+fn build_pattern(
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    max_node_len: usize,
+) -> Result<Regex, WidError> {
     let lc_part = format!(r"(\d{{{w}}})");
-    let time_digits = match time_unit {
-        TimeUnit::Sec => 6,
-        TimeUnit::Ms => 9,
-    };
+    let time_digits = time_unit.time_digits();
     let pad_part = if z > 0 {
         format!(r"(?:-([0-9a-f]{{{z}}}))?$")
     } else {
         r"$".to_string()
     };
-    let pattern = format!(r"^(\d{{8}})T(\d{{{time_digits}}})\.{lc_part}Z-([A-Za-z0-9_]+){pad_part}");
-    Regex::new(&pattern).unwrap()
+    let pattern = format!(
+        r"^(\d{{8}})T(\d{{{time_digits}}})\.{lc_part}Z-([A-Za-z0-9_]{{1,{max_node_len}}}){pad_part}"
+    );
+    Regex::new(&pattern).map_err(|_| WidError::InvalidMaxNodeLen(max_node_len))
+}
+
+pub(crate) fn is_valid_node(node: &str) -> bool {
+    !node.is_empty() && node.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Check `node` against the rule generators enforce: non-empty ASCII
+/// letters, digits and `_`, at most `MAX_NODE_LEN` bytes. Fails with
+/// `WidError::InvalidNode`.
+pub fn validate_node(node: &str) -> Result<(), WidError> {
+    if !is_valid_node(node) || node.len() > MAX_NODE_LEN {
+        return Err(WidError::InvalidNode);
+    }
+    Ok(())
 }
 
-fn parse_ts(time_unit: TimeUnit, date_str: &str, time_str: &str) -> Option<DateTime<Utc>> {
-    let year: i32 = date_str[0..4].parse().ok()?;
-    let month: u32 = date_str[4..6].parse().ok()?;
-    let day: u32 = date_str[6..8].parse().ok()?;
-
-    match time_unit {
-        TimeUnit::Sec => {
-            let hour: u32 = time_str[0..2].parse().ok()?;
-            let minute: u32 = time_str[2..4].parse().ok()?;
-            let second: u32 = time_str[4..6].parse().ok()?;
-            Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
-                .single()
+/// Normalize a raw node name into the HLC-WID node alphabet.
+///
+/// ASCII letters are lowercased, and each run of disallowed characters and
+/// `_` is replaced by a single `_` (`"Node-01"` and `"node_-01"` both become
+/// `"node_01"`). The result is truncated to `MAX_NODE_LEN` bytes so the IDs
+/// it produces stay parseable. Inputs with no alphanumeric character at all
+/// are rejected with `WidError::InvalidNode`.
+pub fn canonicalize_node(raw: &str) -> Result<String, WidError> {
+    let mut out = String::with_capacity(raw.len().min(MAX_NODE_LEN));
+    let mut in_run = false;
+    for c in raw.chars() {
+        if out.len() == MAX_NODE_LEN {
+            break;
         }
-        TimeUnit::Ms => {
-            let hour: u32 = time_str[0..2].parse().ok()?;
-            let minute: u32 = time_str[2..4].parse().ok()?;
-            let second: u32 = time_str[4..6].parse().ok()?;
-            let millis: u32 = time_str[6..9].parse().ok()?;
-            Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
-                .single()?
-                .with_nanosecond(millis * 1_000_000)
+        if c.is_ascii_alphanumeric() {
+            out.push(c.to_ascii_lowercase());
+            in_run = false;
+        } else if !in_run {
+            out.push('_');
+            in_run = true;
         }
     }
+    if !out.chars().any(|c| c.is_ascii_alphanumeric()) {
+        return Err(WidError::InvalidNode);
+    }
+    Ok(out)
 }
 
-fn is_valid_node(node: &str) -> bool {
-    !node.is_empty() && node.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+/// Median estimate of a peer's clock offset from `(remote_pt, local_recv_tick)`
+/// samples, in ticks.
+///
+/// Positive means the peer runs ahead. Each sample's offset includes the
+/// one-way network delay, so the estimate is biased by the typical latency;
+/// the median keeps a few delayed samples from skewing it. Returns 0 for an
+/// empty batch. Offsets are computed in `i128` and the result is clamped to
+/// the `i64` range, so extreme samples cannot overflow.
+pub fn estimate_offset(samples: &[(i64, i64)]) -> i64 {
+    let mut offsets: Vec<i128> = samples
+        .iter()
+        .map(|&(remote_pt, local_recv)| i128::from(remote_pt) - i128::from(local_recv))
+        .collect();
+    if offsets.is_empty() {
+        return 0;
+    }
+    offsets.sort_unstable();
+    let mid = offsets.len() / 2;
+    let median = if offsets.len() % 2 == 1 {
+        offsets[mid]
+    } else {
+        (offsets[mid - 1] + offsets[mid]).div_euclid(2)
+    };
+    median.clamp(i128::from(i64::MIN), i128::from(i64::MAX)) as i64
+}
+
+/// Stable RGB color for a node name, for grouping IDs visually.
+///
+/// Taken from the first three bytes of the node's SHA-256, so every tool
+/// derives the same color for the same node.
+pub fn node_color(node: &str) -> (u8, u8, u8) {
+    let digest = Sha256::digest(node.as_bytes());
+    (digest[0], digest[1], digest[2])
+}
+
+/// `node_color` as a `#rrggbb` hex string.
+pub fn node_badge(node: &str) -> String {
+    let (r, g, b) = node_color(node);
+    format!("#{r:02x}{g:02x}{b:02x}")
 }
 
 /// Validate a HLC-WID string for a specific time unit.
@@ -84,22 +217,61 @@ pub fn parse_hlc_wid_with_unit(
     z: usize,
     time_unit: TimeUnit,
 ) -> Result<ParsedHlcWid, WidError> {
+    parse_hlc_wid_with_max_node(wid, w, z, time_unit, MAX_NODE_LEN)
+}
+
+/// Parse an HLC-WID string, accepting nodes of at most `max_node_len` bytes.
+///
+/// Inputs longer than any ID the config could produce are rejected before the
+/// regex runs, so oversized nodes fail in constant time. `max_node_len` must
+/// be in `1..=MAX_NODE_LEN_LIMIT`, else `WidError::InvalidMaxNodeLen`.
+pub fn parse_hlc_wid_with_max_node(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    max_node_len: usize,
+) -> Result<ParsedHlcWid, WidError> {
+    if wid.trim().is_empty() {
+        return Err(WidError::Empty);
+    }
     if w == 0 || w > crate::wid::MAX_W {
         return Err(WidError::InvalidW);
     }
     if z > crate::wid::MAX_Z {
         return Err(WidError::InvalidZ);
     }
+    if !(1..=MAX_NODE_LEN_LIMIT).contains(&max_node_len) {
+        return Err(WidError::InvalidMaxNodeLen(max_node_len));
+    }
+
+    // date "T" time "." lc "Z-" node ["-" pad]
+    let max_len = 8 + 1 + time_unit.time_digits() + 1 + w + 2 + max_node_len + 1 + z;
+    if wid.len() > max_len {
+        return Err(WidError::InvalidFormat(wid.to_string()));
+    }
+    crate::wid::check_timestamp_lengths(wid, time_unit)?;
 
-    let pattern = if w == 4 && z == 0 && time_unit == TimeUnit::Sec {
+    let default_shape = w == 4 && z == 0 && time_unit == TimeUnit::Sec;
+    let pattern = if default_shape && max_node_len == MAX_NODE_LEN {
         &*HLC_PATTERN_W4_Z0_SEC
     } else {
-        &build_pattern(w, z, time_unit)
+        &build_pattern(w, z, time_unit, max_node_len)?
     };
 
-    let caps = pattern
-        .captures(wid)
-        .ok_or_else(|| WidError::InvalidFormat(wid.to_string()))?;
+    let Some(caps) = pattern.captures(wid) else {
+        // With Z=0 a trailing hex `-segment` is padding the caller did not
+        // configure; name it rather than report a generic format error.
+        if z == 0
+            && let Some((head, tail)) = wid.rsplit_once('-')
+            && !tail.is_empty()
+            && tail.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+            && pattern.is_match(head)
+        {
+            return Err(WidError::UnexpectedPadding(tail.to_string()));
+        }
+        return Err(WidError::InvalidFormat(wid.to_string()));
+    };
 
     let date_str = &caps[1];
     let time_str = &caps[2];
@@ -115,8 +287,9 @@ pub fn parse_hlc_wid_with_unit(
         return Err(WidError::InvalidNode);
     }
 
-    let timestamp = parse_ts(time_unit, date_str, time_str).ok_or(WidError::InvalidTimestamp)?;
-    let logical_counter: u32 = lc_str
+    let timestamp =
+        parse_timestamp(time_unit, date_str, time_str).ok_or(WidError::InvalidTimestamp)?;
+    let logical_counter: u64 = lc_str
         .parse()
         .map_err(|_| WidError::InvalidFormat(wid.to_string()))?;
 
@@ -136,11 +309,36 @@ pub fn parse_hlc_wid(wid: &str, w: usize, z: usize) -> Result<ParsedHlcWid, WidE
 
 /// HLC generator state.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct HLCState {
     pub pt: i64,
     pub lc: i64,
 }
 
+/// Divergence between two HLC states, as returned by `HLCState::diff`.
+///
+/// Deltas are `i128` so that any pair of `i64` states has an exact difference.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct HLCStateDiff {
+    /// `self.pt - other.pt`.
+    pub pt_delta: i128,
+    /// `self.lc - other.lc`.
+    pub lc_delta: i128,
+    /// HLC ordering of `self` relative to `other` (`Greater` means `self` is ahead).
+    pub ahead: Ordering,
+}
+
+impl HLCState {
+    /// Compare two states under HLC ordering (`pt` first, then `lc`).
+    pub fn diff(&self, other: &HLCState) -> HLCStateDiff {
+        HLCStateDiff {
+            pt_delta: i128::from(self.pt) - i128::from(other.pt),
+            lc_delta: i128::from(self.lc) - i128::from(other.lc),
+            ahead: (self.pt, self.lc).cmp(&(other.pt, other.lc)),
+        }
+    }
+}
+
 /// HLC-WID generator.
 pub struct HLCWidGen {
     w: usize,
@@ -152,8 +350,28 @@ pub struct HLCWidGen {
     lc: i64,
     cached_tick: i64,
     cached_ts: String,
+    recent_bumps: VecDeque<Instant>,
+    merges: VecDeque<MergeRecord>,
+    clock: Box<dyn Clock>,
+    max_drift: Option<Duration>,
+}
+
+/// One remote state merged by `HLCWidGen::observe_from`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeRecord {
+    pub node: String,
+    pub pt: i64,
+    pub lc: i64,
 }
 
+/// Most merges kept by `HLCWidGen::merge_history`.
+const MERGE_HISTORY_LEN: usize = 32;
+
+/// How far back `HLCWidGen::counter_rate` looks.
+const RATE_WINDOW: Duration = Duration::from_secs(1);
+/// Most same-tick bumps kept for `HLCWidGen::counter_rate`.
+const RATE_SAMPLES: usize = 256;
+
 impl HLCWidGen {
     /// Create a new HLC-WID generator in `sec` mode.
     pub fn new(node: String, w: usize, z: usize) -> Result<Self, WidError> {
@@ -168,15 +386,11 @@ impl HLCWidGen {
         time_unit: TimeUnit,
     ) -> Result<Self, WidError> {
         // W > MAX_W would overflow the i64 logical counter (10^19 > i64::MAX).
-        if w == 0 || w > crate::wid::MAX_W {
-            return Err(WidError::InvalidW);
-        }
+        crate::wid::check_generator_w(w)?;
         if z > crate::wid::MAX_Z {
             return Err(WidError::InvalidZ);
         }
-        if !is_valid_node(&node) {
-            return Err(WidError::InvalidNode);
-        }
+        validate_node(&node)?;
 
         Ok(Self {
             w,
@@ -188,32 +402,63 @@ impl HLCWidGen {
             lc: 0,
             cached_tick: -1,
             cached_ts: String::new(),
+            recent_bumps: VecDeque::with_capacity(RATE_SAMPLES),
+            merges: VecDeque::new(),
+            clock: Box::new(SystemClock(time_unit)),
+            max_drift: None,
         })
     }
 
-    fn current_tick(time_unit: TimeUnit) -> i64 {
-        let dur = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        match time_unit {
-            TimeUnit::Sec => dur.as_secs() as i64,
-            TimeUnit::Ms => dur.as_millis() as i64,
-        }
+    /// Create a generator whose `try_next_hlc_wid` refuses to run `pt` more
+    /// than `max_drift` away from the clock.
+    ///
+    /// `pt` only gets ahead of the clock through `observe` of a peer with a
+    /// fast clock or through logical-counter rollover, so the threshold is
+    /// really a bound on how far a misconfigured peer (or a broken local NTP)
+    /// may drag this node. Drift is measured in whole ticks: in `sec` mode
+    /// anything under a second reads as zero, so use a few seconds (5-30s
+    /// covers NTP slew plus occasional rollover); in `ms` mode 100ms-1s suits
+    /// NTP-synced hosts.
+    pub fn new_with_max_drift(
+        node: String,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        max_drift: Duration,
+    ) -> Result<Self, WidError> {
+        let mut generator = Self::new_with_time_unit(node, w, z, time_unit)?;
+        generator.max_drift = Some(max_drift);
+        Ok(generator)
+    }
+
+    /// Create a generator that reads physical time from `clock`; see
+    /// `WidGen::with_clock`.
+    pub fn with_clock(
+        node: String,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        clock: Box<dyn Clock>,
+    ) -> Result<Self, WidError> {
+        let mut generator = Self::new_with_time_unit(node, w, z, time_unit)?;
+        generator.clock = clock;
+        Ok(generator)
+    }
+
+    /// Create a generator after normalizing `raw_node` with `canonicalize_node`.
+    pub fn new_with_canonical_node(
+        raw_node: &str,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+    ) -> Result<Self, WidError> {
+        Self::new_with_time_unit(canonicalize_node(raw_node)?, w, z, time_unit)
     }
 
     fn ts_for_tick(&mut self, tick: i64) -> &str {
         if tick != self.cached_tick {
             self.cached_tick = tick;
-            self.cached_ts = match self.time_unit {
-                TimeUnit::Sec => {
-                    let dt = Utc.timestamp_opt(tick, 0).unwrap();
-                    dt.format("%Y%m%dT%H%M%S").to_string()
-                }
-                TimeUnit::Ms => {
-                    let sec = tick / 1000;
-                    let ms = (tick % 1000) as u32;
-                    let dt = Utc.timestamp_opt(sec, ms * 1_000_000).unwrap();
-                    dt.format("%Y%m%dT%H%M%S%3f").to_string()
-                }
-            };
+            self.cached_ts = format_tick(self.time_unit, tick);
         }
         &self.cached_ts
     }
@@ -231,7 +476,7 @@ impl HLCWidGen {
             return Err(WidError::InvalidRemoteClock);
         }
 
-        let now = Self::current_tick(self.time_unit);
+        let now = self.clock.now_tick();
         let new_pt = now.max(self.pt).max(remote_pt);
 
         if new_pt == self.pt && new_pt == remote_pt {
@@ -249,14 +494,73 @@ impl HLCWidGen {
         Ok(())
     }
 
+    /// Merge remote HLC state and remember which node it came from.
+    ///
+    /// Only successful merges are recorded; the last 32 are kept.
+    pub fn observe_from(
+        &mut self,
+        node: &str,
+        remote_pt: i64,
+        remote_lc: i64,
+    ) -> Result<(), WidError> {
+        if !is_valid_node(node) {
+            return Err(WidError::InvalidNode);
+        }
+        self.observe(remote_pt, remote_lc)?;
+        if self.merges.len() == MERGE_HISTORY_LEN {
+            self.merges.pop_front();
+        }
+        self.merges.push_back(MergeRecord {
+            node: node.to_string(),
+            pt: remote_pt,
+            lc: remote_lc,
+        });
+        Ok(())
+    }
+
+    /// Parse a remote HLC-WID with this generator's `w`, `z` and time unit
+    /// and merge its clock via `observe_from`, recording its node.
+    pub fn observe_wid(&mut self, remote: &str) -> Result<(), WidError> {
+        let parsed = parse_hlc_wid_with_unit(remote, self.w, self.z, self.time_unit)?;
+        let pt = self
+            .time_unit
+            .tick_of(parsed.timestamp)
+            .ok_or(WidError::InvalidTimestamp)?;
+        let lc = i64::try_from(parsed.logical_counter).map_err(|_| WidError::InvalidRemoteClock)?;
+        self.observe_from(&parsed.node, pt, lc)
+    }
+
+    /// `observe_wid` for each of `remotes` in order; one result per input, so
+    /// a bad peer ID does not stop the rest from merging.
+    pub fn observe_many_wids(&mut self, remotes: &[&str]) -> Vec<Result<(), WidError>> {
+        remotes
+            .iter()
+            .map(|remote| self.observe_wid(remote))
+            .collect()
+    }
+
+    /// Recent `observe_from` merges, oldest first.
+    pub fn merge_history(&self) -> impl ExactSizeIterator<Item = &MergeRecord> {
+        self.merges.iter()
+    }
+
+    /// Generate the next HLC-WID and record it in the process-wide registry;
+    /// see `WidGen::next_unique_wid`.
+    pub fn next_unique_hlc_wid(&mut self) -> Result<String, WidError> {
+        let wid = self.next_hlc_wid();
+        crate::registry::claim(&wid)?;
+        Ok(wid)
+    }
+
     /// Generate the next HLC-WID.
     pub fn next_hlc_wid(&mut self) -> String {
-        let now = Self::current_tick(self.time_unit);
+        let now = self.clock.now_tick();
         if now > self.pt {
             self.pt = now;
             self.lc = 0;
         } else {
             self.lc += 1;
+            self.record_bump();
         }
         self.rollover_if_needed();
 
@@ -278,6 +582,97 @@ impl HLCWidGen {
         wid
     }
 
+    /// Like `next_hlc_wid`, but fails with `WidError::ClockDrift` instead of
+    /// issuing an ID whose `pt` would sit more than the `new_with_max_drift`
+    /// threshold from the clock. The state is left untouched on error, so the
+    /// call can be retried once the clock catches up. Without a threshold it
+    /// never fails.
+    pub fn try_next_hlc_wid(&mut self) -> Result<String, WidError> {
+        if let Some(max) = self.max_drift {
+            let now = self.clock.now_tick();
+            let next_pt = if now > self.pt {
+                now
+            } else if self.lc >= self.max_lc {
+                self.pt + 1
+            } else {
+                self.pt
+            };
+            let drift = self.time_unit.duration_of_ticks(next_pt.abs_diff(now));
+            if drift > max {
+                return Err(WidError::ClockDrift { drift, max });
+            }
+        }
+        Ok(self.next_hlc_wid())
+    }
+
+    /// How far `pt` is from the clock right now, in either direction.
+    ///
+    /// Only meaningful once the generator has issued, observed or restored
+    /// some state; a fresh generator's `pt` is the epoch.
+    pub fn drift(&self) -> Duration {
+        let now = self.clock.now_tick();
+        self.time_unit.duration_of_ticks(now.abs_diff(self.pt))
+    }
+
+    /// Whether `drift` is over `threshold`.
+    pub fn exceeds_drift_threshold(&self, threshold: Duration) -> bool {
+        self.drift() > threshold
+    }
+
+    /// Like `next_hlc_wid`, but return the fields alongside the ID, built from
+    /// the clock state rather than by parsing it back.
+    pub fn next_parsed_hlc(&mut self) -> ParsedHlcWid {
+        let raw = self.next_hlc_wid();
+        ParsedHlcWid {
+            timestamp: self
+                .time_unit
+                .datetime_of(self.pt)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            logical_counter: self.lc as u64,
+            node: self.node.clone(),
+            padding: (self.z > 0).then(|| raw[raw.len() - self.z..].to_string()),
+            raw,
+        }
+    }
+
+    fn record_bump(&mut self) {
+        let now = Instant::now();
+        if self.recent_bumps.len() == RATE_SAMPLES {
+            self.recent_bumps.pop_front();
+        }
+        while self
+            .recent_bumps
+            .front()
+            .is_some_and(|t| now.duration_since(*t) > RATE_WINDOW)
+        {
+            self.recent_bumps.pop_front();
+        }
+        self.recent_bumps.push_back(now);
+    }
+
+    /// Logical-counter bumps per second from `next_hlc_wid` calls that stayed
+    /// on the same tick, estimated over the last second (at most the last 256
+    /// bumps). `0.0` when fewer than two bumps fall in the window.
+    ///
+    /// A high rate means callers are outpacing the clock and the counter is
+    /// heading for rollover, which pushes `pt` ahead of real time.
+    pub fn counter_rate(&self) -> f64 {
+        let now = Instant::now();
+        let mut recent = self
+            .recent_bumps
+            .iter()
+            .filter(|t| now.duration_since(**t) <= RATE_WINDOW);
+        let Some(first) = recent.next() else {
+            return 0.0;
+        };
+        let (count, last) = recent.fold((1usize, first), |(n, _), t| (n + 1, t));
+        if count < 2 {
+            return 0.0;
+        }
+        let span = last.duration_since(*first).as_secs_f64().max(1e-9);
+        (count - 1) as f64 / span
+    }
+
     /// Generate n HLC-WIDs.
     pub fn next_n(&mut self, n: usize) -> Vec<String> {
         self.take(n).collect()
@@ -305,6 +700,31 @@ impl HLCWidGen {
     pub fn time_unit(&self) -> TimeUnit {
         self.time_unit
     }
+
+    /// Whether `other` has the same `w`, `z`, time unit and node, ignoring
+    /// clock state and merge history.
+    pub fn config_eq(&self, other: &HLCWidGen) -> bool {
+        self.w == other.w
+            && self.z == other.z
+            && self.time_unit == other.time_unit
+            && self.node == other.node
+    }
+}
+
+/// Config and clock state only; the formatted-timestamp cache, rate samples
+/// and merge history are omitted.
+impl std::fmt::Debug for HLCWidGen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("HLCWidGen")
+            .field("node", &self.node)
+            .field("w", &self.w)
+            .field("z", &self.z)
+            .field("time_unit", &self.time_unit)
+            .field("pt", &self.pt)
+            .field("lc", &self.lc)
+            .field("max_drift", &self.max_drift)
+            .finish_non_exhaustive()
+    }
 }
 
 impl Iterator for HLCWidGen {
@@ -315,6 +735,32 @@ impl Iterator for HLCWidGen {
     }
 }
 
+/// Interleave one `HLCWidGen` per node, yielding from each in turn.
+///
+/// Useful for simulating a multi-node cluster in one process: every node's
+/// sub-sequence is produced by its own generator and stays monotone. An empty
+/// `nodes` slice is rejected with `WidError::InvalidNode`.
+pub fn round_robin(
+    nodes: &[&str],
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<impl Iterator<Item = String>, WidError> {
+    let mut gens = nodes
+        .iter()
+        .map(|node| HLCWidGen::new_with_time_unit(node.to_string(), w, z, time_unit))
+        .collect::<Result<Vec<_>, _>>()?;
+    if gens.is_empty() {
+        return Err(WidError::InvalidNode);
+    }
+    let mut next = 0;
+    Ok(std::iter::from_fn(move || {
+        let id = gens[next].next_hlc_wid();
+        next = (next + 1) % gens.len();
+        Some(id)
+    }))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -385,6 +831,11 @@ mod tests {
             HLCWidGen::new("node01".to_string(), 0, 0),
             Err(WidError::InvalidW)
         ));
+        assert!(HLCWidGen::new("node01".to_string(), 18, 0).is_ok());
+        assert!(matches!(
+            HLCWidGen::new("node01".to_string(), 19, 0),
+            Err(WidError::WTooLarge { max_supported: 18 })
+        ));
     }
 
     #[test]
@@ -423,6 +874,29 @@ mod tests {
         assert!(many[0] < many[1]);
     }
 
+    #[test]
+    fn test_z0_rejects_trailing_padding() {
+        assert!(matches!(
+            parse_hlc_wid("20260212T091530.0042Z-node01-a3f91c", 4, 0),
+            Err(WidError::UnexpectedPadding(pad)) if pad == "a3f91c"
+        ));
+        assert!(matches!(
+            parse_hlc_wid_with_unit("20260212T091530123.0042Z-node01-ab", 4, 0, TimeUnit::Ms),
+            Err(WidError::UnexpectedPadding(_))
+        ));
+        // Non-hex trailing segments are still plain format errors.
+        assert!(matches!(
+            parse_hlc_wid("20260212T091530.0042Z-node01-xyz", 4, 0),
+            Err(WidError::InvalidFormat(_))
+        ));
+        assert_eq!(
+            parse_hlc_wid("20260212T091530.0042Z-node01", 4, 0)
+                .unwrap()
+                .node,
+            "node01"
+        );
+    }
+
     #[test]
     fn test_parse_hlc_invalid_timestamp() {
         assert!(matches!(
@@ -439,6 +913,376 @@ mod tests {
         assert_eq!(p.padding.as_deref(), Some("ab"));
     }
 
+    #[test]
+    fn test_parse_hlc_empty_input() {
+        assert!(matches!(parse_hlc_wid("", 4, 0), Err(WidError::Empty)));
+        assert!(matches!(
+            parse_hlc_wid_with_unit("   ", 4, 6, TimeUnit::Ms),
+            Err(WidError::Empty)
+        ));
+    }
+
+    #[test]
+    fn test_parse_hlc_timestamp_field_length() {
+        assert!(matches!(
+            parse_hlc_wid("20260212T09153.0000Z-node01", 4, 0),
+            Err(WidError::InvalidTimestampLength {
+                expected: 6,
+                found: 5
+            })
+        ));
+    }
+
+    #[test]
+    fn test_node_length_is_bounded() {
+        let node = "n".repeat(MAX_NODE_LEN);
+        let ok = format!("20260212T091530.0000Z-{node}");
+        assert_eq!(parse_hlc_wid(&ok, 4, 0).unwrap().node, node);
+
+        let long = format!("20260212T091530.0000Z-{}", "n".repeat(MAX_NODE_LEN + 1));
+        assert!(!validate_hlc_wid(&long, 4, 0));
+        assert!(parse_hlc_wid_with_max_node(&long, 4, 0, TimeUnit::Sec, 128).is_ok());
+        for bad in [0, MAX_NODE_LEN_LIMIT + 1, 1_000_000, usize::MAX] {
+            assert!(matches!(
+                parse_hlc_wid_with_max_node(&long, 4, 0, TimeUnit::Sec, bad),
+                Err(WidError::InvalidMaxNodeLen(n)) if n == bad
+            ));
+        }
+        let widest = format!("20260212T091530.0000Z-{}", "n".repeat(MAX_NODE_LEN_LIMIT));
+        assert!(
+            parse_hlc_wid_with_max_node(&widest, 4, 0, TimeUnit::Sec, MAX_NODE_LEN_LIMIT).is_ok()
+        );
+
+        let huge = format!("20260212T091530.0000Z-{}", "n".repeat(1 << 20));
+        let start = std::time::Instant::now();
+        assert!(matches!(
+            parse_hlc_wid_with_unit(&huge, 4, 6, TimeUnit::Ms),
+            Err(WidError::InvalidFormat(_))
+        ));
+        // The length pre-check answers in microseconds; the bound only has to
+        // catch a regression to scanning the whole megabyte, with slack for
+        // slow CI machines.
+        assert!(start.elapsed() < std::time::Duration::from_secs(1));
+        assert!(HLCWidGen::new("n".repeat(MAX_NODE_LEN + 1), 4, 0).is_err());
+        let mut longest = HLCWidGen::new(node, 4, 0).unwrap();
+        assert!(validate_hlc_wid(&longest.next_hlc_wid(), 4, 0));
+    }
+
+    #[test]
+    fn test_node_color_is_stable() {
+        assert_eq!(node_color("node01"), node_color("node01"));
+        assert_ne!(node_color("node01"), node_color("node02"));
+        let badge = node_badge("node01");
+        assert_eq!(badge.len(), 7);
+        assert!(badge.starts_with('#'));
+        let (r, g, b) = node_color("node01");
+        assert_eq!(badge, format!("#{r:02x}{g:02x}{b:02x}"));
+    }
+
+    #[test]
+    fn test_counter_rate_tracks_same_tick_bumps() {
+        let mut hlc = HLCWidGen::new("node01".to_string(), 6, 0).unwrap();
+        assert_eq!(hlc.counter_rate(), 0.0);
+
+        // A year-3000 pt keeps every call on the same tick.
+        hlc.restore_state(32_503_680_000, 0).unwrap();
+        hlc.next_n(1000);
+        let rate = hlc.counter_rate();
+        assert!(rate > 0.0 && rate.is_finite());
+        assert!(rate < 1e10);
+        assert_eq!(hlc.recent_bumps.len(), RATE_SAMPLES);
+    }
+
+    #[test]
+    fn test_observe_from_records_sources() {
+        let mut hlc = HLCWidGen::new("local".to_string(), 4, 0).unwrap();
+        hlc.observe_from("alpha", 100, 3).unwrap();
+        hlc.observe_from("beta", 200, 0).unwrap();
+        assert!(hlc.observe_from("gamma", -1, 0).is_err());
+        assert!(hlc.observe_from("bad-node", 1, 0).is_err());
+
+        let nodes: Vec<&str> = hlc.merge_history().map(|m| m.node.as_str()).collect();
+        assert_eq!(nodes, ["alpha", "beta"]);
+        let first = hlc.merge_history().next().unwrap();
+        assert_eq!((first.pt, first.lc), (100, 3));
+
+        for i in 0..40 {
+            hlc.observe_from("alpha", i, 0).unwrap();
+        }
+        assert_eq!(hlc.merge_history().len(), MERGE_HISTORY_LEN);
+    }
+
+    #[test]
+    fn test_drift_limits_try_next() {
+        let mut generator = HLCWidGen::new_with_max_drift(
+            "node01".to_string(),
+            4,
+            0,
+            TimeUnit::Sec,
+            Duration::from_secs(5),
+        )
+        .unwrap();
+        let clock = std::sync::Arc::new(crate::wid::ManualClock::new(1_770_887_730));
+        generator.clock = Box::new(clock.clone());
+
+        generator.try_next_hlc_wid().unwrap();
+        assert_eq!(generator.drift(), Duration::ZERO);
+
+        // A peer five seconds ahead is tolerated, ten is not.
+        generator.observe(1_770_887_735, 0).unwrap();
+        assert_eq!(generator.drift(), Duration::from_secs(5));
+        assert!(!generator.exceeds_drift_threshold(Duration::from_secs(5)));
+        generator.try_next_hlc_wid().unwrap();
+
+        generator.observe(1_770_887_740, 0).unwrap();
+        assert!(generator.exceeds_drift_threshold(Duration::from_secs(5)));
+        let before = generator.state();
+        assert!(matches!(
+            generator.try_next_hlc_wid(),
+            Err(WidError::ClockDrift { drift, max })
+                if drift == Duration::from_secs(10) && max == Duration::from_secs(5)
+        ));
+        assert_eq!(generator.state(), before);
+        // The infallible path still advances.
+        generator.next_hlc_wid();
+
+        clock.set(1_770_887_738);
+        generator.try_next_hlc_wid().unwrap();
+
+        // A clock that jumped ahead of `pt` is drift too, but the next ID
+        // catches `pt` up to it.
+        clock.set(1_770_887_800);
+        assert_eq!(generator.drift(), Duration::from_secs(60));
+        generator.try_next_hlc_wid().unwrap();
+        assert_eq!(generator.drift(), Duration::ZERO);
+    }
+
+    #[test]
+    fn test_drift_in_ms_mode_and_rollover() {
+        let mut generator = HLCWidGen::new_with_max_drift(
+            "node01".to_string(),
+            1,
+            0,
+            TimeUnit::Ms,
+            Duration::from_millis(1),
+        )
+        .unwrap();
+        generator.clock = Box::new(crate::wid::ManualClock::new(1_770_887_730_000));
+        // W=1 rolls over after ten IDs in one tick, pushing `pt` ahead.
+        for _ in 0..20 {
+            generator.try_next_hlc_wid().unwrap();
+        }
+        assert_eq!(generator.drift(), Duration::from_millis(1));
+        // The next rollover would put `pt` two ticks ahead.
+        assert!(matches!(
+            generator.try_next_hlc_wid(),
+            Err(WidError::ClockDrift { drift, .. }) if drift == Duration::from_millis(2)
+        ));
+    }
+
+    #[test]
+    fn test_validate_node() {
+        assert!(validate_node("node_01").is_ok());
+        assert!(matches!(
+            validate_node("node-01"),
+            Err(WidError::InvalidNode)
+        ));
+        assert!(matches!(validate_node(""), Err(WidError::InvalidNode)));
+        assert!(validate_node(&"n".repeat(MAX_NODE_LEN)).is_ok());
+        assert!(validate_node(&"n".repeat(MAX_NODE_LEN + 1)).is_err());
+    }
+
+    #[test]
+    fn test_next_parsed_hlc_matches_raw() {
+        let mut hlc = HLCWidGen::new("node_a".to_string(), 4, 6).unwrap();
+        for _ in 0..3 {
+            let parsed = hlc.next_parsed_hlc();
+            assert_eq!(parsed, parse_hlc_wid(&parsed.raw, 4, 6).unwrap());
+        }
+        let mut ms = HLCWidGen::new_with_time_unit("n".to_string(), 2, 0, TimeUnit::Ms).unwrap();
+        let parsed = ms.next_parsed_hlc();
+        assert_eq!(
+            parsed,
+            parse_hlc_wid_with_unit(&parsed.raw, 2, 0, TimeUnit::Ms).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_parsed_hlc_wid_causal_order() {
+        let parse = |wid: &str| parse_hlc_wid(wid, 4, 0).unwrap();
+        let mut ids = [
+            parse("20260212T091531.0000Z-alpha"),
+            parse("20260212T091530.0002Z-alpha"),
+            parse("20260212T091530.0001Z-gamma"),
+            parse("20260212T091530.0001Z-beta"),
+        ];
+        ids.sort();
+        let raw: Vec<&str> = ids.iter().map(|p| p.raw.as_str()).collect();
+        assert_eq!(
+            raw,
+            [
+                "20260212T091530.0001Z-beta",
+                "20260212T091530.0001Z-gamma",
+                "20260212T091530.0002Z-alpha",
+                "20260212T091531.0000Z-alpha",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_observe_wid_merges_remote_ids() {
+        let mut hlc = HLCWidGen::new("local".to_string(), 4, 6).unwrap();
+        // Far-future remote so the local clock cannot overtake it.
+        hlc.observe_wid("30000101T000000.0007Z-alpha-a3f91c")
+            .unwrap();
+        let state = hlc.state();
+        assert_eq!((state.pt, state.lc), (32_503_680_000, 8));
+        assert_eq!(hlc.merge_history().last().unwrap().node, "alpha");
+        assert!(
+            hlc.next_hlc_wid()
+                .starts_with("30000101T000000.0009Z-local")
+        );
+
+        let results = hlc.observe_many_wids(&["not-a-wid", "30000101T000000.0020Z-beta"]);
+        assert!(results[0].is_err());
+        assert!(results[1].is_ok());
+        assert_eq!(hlc.state().lc, 21);
+    }
+
+    #[test]
+    fn test_large_w_logical_counter_round_trips() {
+        let mut hlc = HLCWidGen::new("n1".to_string(), 12, 0).unwrap();
+        hlc.restore_state(32_503_680_000, 4_999_999_999).unwrap();
+        let id = hlc.next_hlc_wid();
+        let parsed = parse_hlc_wid(&id, 12, 0).unwrap();
+        assert_eq!(parsed.logical_counter, 5_000_000_000);
+    }
+
+    #[test]
+    fn test_estimate_offset_is_median() {
+        // Peer 250 ticks ahead, with jittered delivery and two delayed outliers.
+        let jitter = [-3, 1, 0, 2, -1, 4, -2];
+        let mut samples: Vec<(i64, i64)> = jitter
+            .iter()
+            .enumerate()
+            .map(|(i, j)| {
+                let local = 1_000 + i as i64 * 100;
+                (local + 250 + j, local)
+            })
+            .collect();
+        samples.push((2_000, 3_000));
+        samples.push((2_100, 5_000));
+        assert!((estimate_offset(&samples) - 250).abs() <= 5);
+
+        assert_eq!(estimate_offset(&[(10, 4), (10, 6)]), 5);
+        assert_eq!(estimate_offset(&[]), 0);
+
+        assert_eq!(estimate_offset(&[(i64::MAX, i64::MIN)]), i64::MAX);
+        assert_eq!(estimate_offset(&[(i64::MIN, i64::MAX)]), i64::MIN);
+        let both = [(i64::MAX, 0), (i64::MAX, 1)];
+        assert_eq!(estimate_offset(&both), i64::MAX - 1);
+    }
+
+    #[test]
+    fn test_config_eq_compares_node() {
+        let mut primary = HLCWidGen::new("node_a".to_string(), 4, 6).unwrap();
+        let standby = HLCWidGen::new("node_a".to_string(), 4, 6).unwrap();
+        primary.next_hlc_wid();
+        primary.observe(primary.state().pt + 5, 3).unwrap();
+        assert!(primary.config_eq(&standby));
+
+        let other_node = HLCWidGen::new("node_b".to_string(), 4, 6).unwrap();
+        assert!(!primary.config_eq(&other_node));
+        let other_w = HLCWidGen::new("node_a".to_string(), 5, 6).unwrap();
+        assert!(!primary.config_eq(&other_w));
+        let ms = HLCWidGen::new_with_time_unit("node_a".to_string(), 4, 6, TimeUnit::Ms).unwrap();
+        assert!(!primary.config_eq(&ms));
+    }
+
+    #[test]
+    fn test_canonicalize_node() {
+        assert_eq!(canonicalize_node("Node-01").unwrap(), "node_01");
+        assert_eq!(canonicalize_node("edge.gw--7").unwrap(), "edge_gw_7");
+        assert_eq!(canonicalize_node("node_01").unwrap(), "node_01");
+        assert_eq!(canonicalize_node("a_-b").unwrap(), "a_b");
+        assert_eq!(canonicalize_node("__a__").unwrap(), "_a_");
+
+        let long = canonicalize_node(&"N".repeat(100)).unwrap();
+        assert_eq!(long, "n".repeat(MAX_NODE_LEN));
+        let mut g =
+            HLCWidGen::new_with_canonical_node(&"N".repeat(100), 4, 0, TimeUnit::Sec).unwrap();
+        assert!(validate_hlc_wid(&g.next_hlc_wid(), 4, 0));
+        assert!(matches!(
+            canonicalize_node("--.."),
+            Err(WidError::InvalidNode)
+        ));
+        assert!(matches!(canonicalize_node(""), Err(WidError::InvalidNode)));
+
+        let mut g = HLCWidGen::new_with_canonical_node("Node-01", 4, 0, TimeUnit::Sec).unwrap();
+        assert!(g.next_hlc_wid().ends_with("Z-node_01"));
+    }
+
+    #[test]
+    fn test_state_diff() {
+        let a = HLCState { pt: 100, lc: 3 };
+        let same = a.diff(&HLCState { pt: 100, lc: 3 });
+        assert_eq!((same.pt_delta, same.lc_delta), (0, 0));
+        assert_eq!(same.ahead, Ordering::Equal);
+
+        let pt_ahead = HLCState { pt: 105, lc: 0 }.diff(&a);
+        assert_eq!(pt_ahead.pt_delta, 5);
+        assert_eq!(pt_ahead.lc_delta, -3);
+        assert_eq!(pt_ahead.ahead, Ordering::Greater);
+
+        let lc_ahead = HLCState { pt: 100, lc: 7 }.diff(&a);
+        assert_eq!(lc_ahead.pt_delta, 0);
+        assert_eq!(lc_ahead.lc_delta, 4);
+        assert_eq!(lc_ahead.ahead, Ordering::Greater);
+        assert_eq!(a.diff(&HLCState { pt: 100, lc: 7 }).ahead, Ordering::Less);
+
+        let min = HLCState {
+            pt: i64::MIN,
+            lc: i64::MIN,
+        };
+        let max = HLCState {
+            pt: i64::MAX,
+            lc: i64::MAX,
+        };
+        let extreme = min.diff(&HLCState { pt: 1, lc: 1 });
+        assert_eq!(extreme.pt_delta, i128::from(i64::MIN) - 1);
+        assert_eq!(max.diff(&min).lc_delta, u64::MAX as i128);
+        assert_eq!(extreme.ahead, Ordering::Less);
+    }
+
+    #[test]
+    fn test_round_robin_alternates_nodes() {
+        let ids: Vec<String> = round_robin(&["alpha", "beta"], 4, 0, TimeUnit::Sec)
+            .unwrap()
+            .take(6)
+            .collect();
+        let parsed: Vec<ParsedHlcWid> = ids
+            .iter()
+            .map(|id| parse_hlc_wid(id, 4, 0).unwrap())
+            .collect();
+        for (i, p) in parsed.iter().enumerate() {
+            assert_eq!(p.node, if i % 2 == 0 { "alpha" } else { "beta" });
+        }
+        for node in ["alpha", "beta"] {
+            let own: Vec<&String> = ids.iter().filter(|id| id.ends_with(node)).collect();
+            assert_eq!(own.len(), 3);
+            assert!(own.windows(2).all(|p| p[0] < p[1]));
+        }
+
+        assert!(matches!(
+            round_robin(&[], 4, 0, TimeUnit::Sec).map(|_| ()),
+            Err(WidError::InvalidNode)
+        ));
+        assert!(matches!(
+            round_robin(&["ok", "bad-node"], 4, 0, TimeUnit::Sec).map(|_| ()),
+            Err(WidError::InvalidNode)
+        ));
+    }
+
     #[test]
     fn test_ms_generator_shape() {
         let mut g =
@@ -446,4 +1290,136 @@ mod tests {
         let id = g.next_hlc_wid();
         assert!(validate_hlc_wid_with_unit(&id, 4, 0, TimeUnit::Ms));
     }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let parsed = parse_hlc_wid("20260212T091530.0042Z-node01-a3f91c", 4, 6).unwrap();
+        let json = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(json["timestamp"], "2026-02-12T09:15:30Z");
+        assert_eq!(json["logical_counter"], 42);
+        assert_eq!(json["node"], "node01");
+        let back: ParsedHlcWid = serde_json::from_value(json).unwrap();
+        assert_eq!(back, parsed);
+
+        let state = HLCState {
+            pt: 1_770_888_930,
+            lc: 7,
+        };
+        let json = serde_json::to_string(&state).unwrap();
+        assert_eq!(json, r#"{"pt":1770888930,"lc":7}"#);
+        assert_eq!(serde_json::from_str::<HLCState>(&json).unwrap(), state);
+    }
+
+    #[test]
+    fn test_with_clock_is_deterministic() {
+        let clock = std::sync::Arc::new(crate::ManualClock::new(1_770_888_930));
+        let mut g = HLCWidGen::with_clock(
+            "node01".to_string(),
+            1,
+            0,
+            TimeUnit::Sec,
+            Box::new(clock.clone()),
+        )
+        .unwrap();
+        assert_eq!(g.next_hlc_wid(), "20260212T093530.0Z-node01");
+        assert_eq!(g.next_hlc_wid(), "20260212T093530.1Z-node01");
+        g.next_n(8);
+        // Counter exhausted at W=1: roll into the next physical tick.
+        assert_eq!(g.next_hlc_wid(), "20260212T093531.0Z-node01");
+
+        clock.set(1_770_888_900);
+        assert_eq!(g.next_hlc_wid(), "20260212T093531.1Z-node01");
+        clock.advance(60);
+        assert_eq!(g.next_hlc_wid(), "20260212T093600.0Z-node01");
+    }
+
+    #[test]
+    fn test_debug_hides_caches() {
+        let mut g = HLCWidGen::new("node01".to_string(), 4, 6).unwrap();
+        g.next_hlc_wid();
+        let out = format!("{g:?}");
+        for field in [
+            "node: \"node01\"",
+            "w: 4",
+            "z: 6",
+            "time_unit: Sec",
+            "pt: ",
+            "lc: 0",
+        ] {
+            assert!(out.contains(field), "{out}");
+        }
+        assert!(!out.contains("cached"), "{out}");
+        assert!(!out.contains("merges"), "{out}");
+    }
+
+    #[test]
+    fn test_validate_hlc_wid_in_window() {
+        let minute = Duration::from_secs(60);
+        let skew = Duration::from_secs(5);
+        let mut g = HLCWidGen::new("node01".to_string(), 4, 0).unwrap();
+        let fresh = g.next_hlc_wid();
+        assert!(validate_hlc_wid_in_window(
+            &fresh,
+            4,
+            0,
+            TimeUnit::Sec,
+            minute,
+            skew
+        ));
+
+        let ahead = Utc::now() + chrono::Duration::seconds(30);
+        let id = format!("{}.0000Z-node01", ahead.format("%Y%m%dT%H%M%S"));
+        assert!(!validate_hlc_wid_in_window(
+            &id,
+            4,
+            0,
+            TimeUnit::Sec,
+            minute,
+            skew
+        ));
+        assert!(validate_hlc_wid_in_window(
+            &id,
+            4,
+            0,
+            TimeUnit::Sec,
+            minute,
+            minute
+        ));
+
+        let old = "20200101T000000.0000Z-node01";
+        assert!(!validate_hlc_wid_in_window(
+            old,
+            4,
+            0,
+            TimeUnit::Sec,
+            minute,
+            skew
+        ));
+    }
+
+    #[test]
+    fn test_elapsed_and_freshness() {
+        let old = parse_hlc_wid("20200101T000000.0000Z-node01", 4, 0).unwrap();
+        assert!(old.age_secs() > 365 * 86_400);
+        assert!(old.is_older_than(60));
+
+        let ahead = Utc::now() + chrono::Duration::seconds(30);
+        let id = format!("{}.0000Z-node01", ahead.format("%Y%m%dT%H%M%S"));
+        let skewed = parse_hlc_wid(&id, 4, 0).unwrap();
+        // Sub-second truncation and a slow test run shave off up to ~2s.
+        assert!((-30..=-27).contains(&skewed.age_secs()));
+        assert!(!skewed.is_older_than(0));
+    }
+
+    #[test]
+    fn test_ns_generator_shape() {
+        let mut g =
+            HLCWidGen::new_with_time_unit("node01".to_string(), 4, 6, TimeUnit::Ns).unwrap();
+        let id = g.next_hlc_wid();
+        assert_eq!(id.find('.'), Some(24));
+        let parsed = parse_hlc_wid_with_unit(&id, 4, 6, TimeUnit::Ns).unwrap();
+        assert_eq!(parsed.node, "node01");
+        assert!(parse_hlc_wid_with_unit(&id, 4, 6, TimeUnit::Ms).is_err());
+    }
 }
@@ -0,0 +1,87 @@
+//! Generator abstraction for code that should not depend on a concrete ID source.
+
+use std::collections::VecDeque;
+
+use crate::{CounterWidGen, HLCWidGen, WidGen};
+
+/// A source of unique IDs.
+pub trait IdGenerator {
+    /// Produce the next ID.
+    fn next_id(&mut self) -> String;
+}
+
+impl IdGenerator for WidGen {
+    fn next_id(&mut self) -> String {
+        self.next_wid()
+    }
+}
+
+impl IdGenerator for HLCWidGen {
+    fn next_id(&mut self) -> String {
+        self.next_hlc_wid()
+    }
+}
+
+impl IdGenerator for CounterWidGen {
+    fn next_id(&mut self) -> String {
+        self.next_counter_wid()
+    }
+}
+
+/// Test double that hands out a preset sequence of IDs.
+#[derive(Debug, Clone, Default)]
+pub struct FixedIdGen {
+    ids: VecDeque<String>,
+}
+
+impl FixedIdGen {
+    /// Create a generator that returns `ids` in order.
+    pub fn new<I, S>(ids: I) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: Into<String>,
+    {
+        Self {
+            ids: ids.into_iter().map(Into::into).collect(),
+        }
+    }
+
+    /// Number of preset IDs not yet handed out.
+    pub fn remaining(&self) -> usize {
+        self.ids.len()
+    }
+}
+
+impl IdGenerator for FixedIdGen {
+    /// # Panics
+    ///
+    /// Panics once the preset sequence is exhausted, so a test that draws more
+    /// IDs than it planned for fails loudly.
+    fn next_id(&mut self) -> String {
+        self.ids.pop_front().expect("FixedIdGen exhausted")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::validate_wid;
+
+    fn label_all<G: IdGenerator>(id_gen: &mut G, names: &[&str]) -> Vec<String> {
+        names
+            .iter()
+            .map(|name| format!("{name}:{}", id_gen.next_id()))
+            .collect()
+    }
+
+    #[test]
+    fn test_generic_caller_accepts_fake_and_real() {
+        let mut fake = FixedIdGen::new(["id-1", "id-2"]);
+        assert_eq!(label_all(&mut fake, &["a", "b"]), vec!["a:id-1", "b:id-2"]);
+        assert_eq!(fake.remaining(), 0);
+
+        let mut real = WidGen::default_params();
+        let labeled = label_all(&mut real, &["a"]);
+        assert!(validate_wid(labeled[0].strip_prefix("a:").unwrap(), 4, 6));
+    }
+}
@@ -0,0 +1,71 @@
+//! Pluggable string layouts for generated IDs.
+//!
+//! A generator always produces the same three fields: the canonical timestamp
+//! (`20260212T091530`, plus `ms`/`ns` digits), the zero-padded sequence, and
+//! the optional padding. A `WidFormat` decides how they are joined; a
+//! `WidFormatParser` splits a formatted ID back into them so it can be
+//! validated like a canonical WID.
+
+/// Joins the fields of a generated ID into its final string.
+///
+/// Implementations should emit `ts` first and unchanged, and keep `seq`
+/// fixed-width right after it: that is what keeps IDs from one generator in
+/// lexicographic order. Anything after that is free-form. `Send + Sync`
+/// keeps `WidGen`, which boxes its format, shareable across threads.
+pub trait WidFormat: Send + Sync {
+    fn format(&self, ts: &str, seq: &str, pad: Option<&str>) -> String;
+}
+
+/// Inverse of a `WidFormat`: recovers the `(ts, seq, pad)` fields from an ID
+/// in that layout, or `None` if the string is not in it.
+///
+/// Fields are only split here; `parse_wid_with_format` checks their contents.
+pub trait WidFormatParser {
+    fn split<'a>(&self, wid: &'a str) -> Option<(&'a str, &'a str, Option<&'a str>)>;
+}
+
+/// The standard `TIMESTAMP "." SEQ "Z" [ "-" PAD ]` layout.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct CanonicalFormat;
+
+impl WidFormat for CanonicalFormat {
+    fn format(&self, ts: &str, seq: &str, pad: Option<&str>) -> String {
+        match pad {
+            Some(pad) => format!("{ts}.{seq}Z-{pad}"),
+            None => format!("{ts}.{seq}Z"),
+        }
+    }
+}
+
+impl WidFormatParser for CanonicalFormat {
+    fn split<'a>(&self, wid: &'a str) -> Option<(&'a str, &'a str, Option<&'a str>)> {
+        let (ts, rest) = wid.split_once('.')?;
+        let (seq, rest) = rest.split_once('Z')?;
+        match rest {
+            "" => Some((ts, seq, None)),
+            _ => Some((ts, seq, Some(rest.strip_prefix('-')?))),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_format_round_trip() {
+        for (pad, wid) in [
+            (Some("a3f91c"), "20260212T091530.0042Z-a3f91c"),
+            (None, "20260212T091530.0042Z"),
+        ] {
+            let formatted = CanonicalFormat.format("20260212T091530", "0042", pad);
+            assert_eq!(formatted, wid);
+            assert_eq!(
+                CanonicalFormat.split(wid),
+                Some(("20260212T091530", "0042", pad))
+            );
+        }
+        assert_eq!(CanonicalFormat.split("20260212T091530.0042Zx"), None);
+        assert_eq!(CanonicalFormat.split("20260212T091530"), None);
+    }
+}
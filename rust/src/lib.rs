@@ -21,17 +21,55 @@
 //! ```
 
 mod async_api;
+mod counter;
+mod frame;
 mod hlc;
+mod id_gen;
+mod layout;
 mod manifest;
+mod monitor;
+mod otp;
+mod padding;
+mod pool;
+mod registry;
+mod sign;
+mod stream;
 mod wid;
 
-pub use async_api::{async_hlc_wid_stream, async_next_hlc_wid, async_next_wid, async_wid_stream};
+pub use async_api::{
+    HLCStream, WidStream, async_hlc_wid_stream, async_next_hlc_wid, async_next_wid,
+    async_wid_stream, wid_stream_with_clock,
+};
+pub use counter::CounterWidGen;
+pub use frame::{pack_frame, unpack_frame};
 pub use hlc::{
-    HLCState, HLCWidGen, ParsedHlcWid, parse_hlc_wid, parse_hlc_wid_with_unit, validate_hlc_wid,
-    validate_hlc_wid_with_unit,
+    HLCState, HLCStateDiff, HLCWidGen, MAX_NODE_LEN, MAX_NODE_LEN_LIMIT, MergeRecord, ParsedHlcWid,
+    canonicalize_node, estimate_offset, node_badge, node_color, parse_hlc_wid,
+    parse_hlc_wid_with_max_node, parse_hlc_wid_with_unit, round_robin, validate_hlc_wid,
+    validate_hlc_wid_in_window, validate_hlc_wid_with_unit, validate_node,
+};
+pub use id_gen::{FixedIdGen, IdGenerator};
+pub use layout::{CanonicalFormat, WidFormat, WidFormatParser};
+pub use manifest::{
+    CHUNK_INDEX_KEY, CHUNK_TOTAL_KEY, DataType, GENERATOR_TIME_UNIT_KEY, GENERATOR_W_KEY,
+    GENERATOR_Z_KEY, HashAlgorithm, MANIFEST_MAGIC, MANIFEST_VERSION, Manifest, ManifestError,
+    MergePolicy, PARENT_ID_KEY, SynapseFile,
 };
-pub use manifest::{DataType, MANIFEST_MAGIC, MANIFEST_VERSION, Manifest, SynapseFile};
+pub use monitor::RateMonitor;
+pub use otp::compute_wotp;
+pub use padding::{HEX_ALPHABET, HexPadding, PaddingMode, PaddingProvider};
+pub use pool::WidPool;
+pub use registry::enable_global_uniqueness;
+pub use sign::{sign_wid, signed_message, verify_signed};
+pub use stream::{StreamOpts, run_stream_with_progress};
 pub use wid::{
-    MAX_W, MAX_Z, ParsedWid, TimeUnit, WidError, WidGen, parse_wid, parse_wid_with_unit,
-    validate_wid, validate_wid_with_unit,
+    Clock, CoverageReport, Granularity, MAX_TICK_MS, MAX_TICK_NS, MAX_TICK_SEC, MAX_W, MAX_Z,
+    MIN_BOUNDED_YEAR, ManualClock, ParsedWid, ParsedWidRef, PartialWid, Reservation, SharedWidGen,
+    SystemClock, TimeUnit, TryIter, WID_FORMAT, WidColumns, WidError, WidFields, WidGen,
+    WidGenBuilder, age_buckets, before, capture_fields, coarsen, config_covers, convert_time_unit,
+    day_bounds, extract_version_tag, hash_for_bloom, max_wid_at, min_w_for_rate, min_wid_at,
+    next_second_boundary, parse_batch, parse_wid, parse_wid_bounded, parse_wid_descending,
+    parse_wid_partial, parse_wid_ref, parse_wid_with_format, parse_wid_with_unit, renumber,
+    same_generator_plausible, stable_sort, strip_padding, to_columns, validate_wid,
+    validate_wid_in_window, validate_wid_with_unit,
 };
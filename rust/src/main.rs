@@ -1,32 +1,32 @@
 use std::env;
 use std::fs::{self, OpenOptions};
-use std::io::{self, Write};
+use std::io::{self, BufRead, BufWriter, Write};
 use std::path::{Path, PathBuf};
 use std::process::{self, Command, Stdio};
 use std::thread;
 use std::time::{Duration, Instant};
 
-use base64::Engine as _;
-use base64::engine::general_purpose::URL_SAFE_NO_PAD;
-use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
-use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
-use hmac::{Hmac, Mac};
+use rand::random_range;
 use serde_json::json;
-use sha2::Sha256;
 use subtle::ConstantTimeEq;
 use wid::{
-    HLCWidGen, TimeUnit, WidGen, parse_hlc_wid_with_unit, parse_wid_with_unit,
-    validate_hlc_wid_with_unit, validate_wid_with_unit,
+    HLCWidGen, MANIFEST_VERSION, StreamOpts, TimeUnit, WID_FORMAT, WidError, WidGen, compute_wotp,
+    convert_time_unit, parse_hlc_wid_with_unit, parse_wid_with_unit, run_stream_with_progress,
+    sign_wid, validate_hlc_wid_in_window, validate_hlc_wid_with_unit, validate_wid_in_window,
+    validate_wid_with_unit, verify_signed,
 };
 
-type HmacSha256 = Hmac<Sha256>;
-
 #[derive(Debug, Clone)]
 struct ValidateOpts {
     kind: String,
     w: usize,
     z: usize,
     time_unit: TimeUnit,
+    /// When set, also require the timestamp to be at most this old.
+    max_age_sec: Option<u64>,
+    /// When set, also require the timestamp to be at most this far ahead.
+    /// Defaults to 5s once `max_age_sec` enables the window check.
+    max_future_sec: Option<u64>,
 }
 
 impl Default for ValidateOpts {
@@ -36,6 +36,8 @@ impl Default for ValidateOpts {
             w: 4,
             z: 6,
             time_unit: TimeUnit::Sec,
+            max_age_sec: None,
+            max_future_sec: None,
         }
     }
 }
@@ -73,6 +75,7 @@ struct CanonOpts {
     digits: usize,
     max_age_sec: u64,
     max_future_sec: u64,
+    node: String,
 }
 
 fn default_node() -> String {
@@ -82,15 +85,15 @@ fn default_node() -> String {
 fn print_help() {
     eprintln!(
         "wid - WID/HLC-WID generator CLI\n\n\
-Usage:\n  wid next [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms]\n  wid stream [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms] [--count <n>]\n  wid validate <id> [--kind wid|hlc] [--W <n>] [--Z <n>] [--time-unit sec|ms]\n  wid parse <id> [--kind wid|hlc] [--W <n>] [--Z <n>] [--time-unit sec|ms] [--json]\n  wid healthcheck [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms] [--json]\n  wid bench [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms] [--count <n>]\n\
-Canonical mode:\n  wid W=# A=# L=# D=# I=# E=# Z=# T=sec|ms R=auto|mqtt|ws|redis|null|stdout N=#\n  wid A=w-otp MODE=gen|verify KEY=<secret|path> [WID=<wid>] [CODE=<otp>] [DIGITS=6] [MAX_AGE_SEC=0] [MAX_FUTURE_SEC=5]\n  For A=stream: N=0 means infinite stream\n  E supports: state | stateless | sql\n"
+Usage:\n  wid next [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] [--count <n>] [--json]\n  wid stream [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] [--count <n>] [--buffer <n>]\n  wid validate <id> [--kind wid|hlc] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] [--max-age-sec <n>] [--max-future-sec <n>]\n  wid parse <id> [--kind wid|hlc] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] [--json]\n  wid healthcheck [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] [--json]\n  wid bench [--kind wid|hlc] [--node <name>] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] [--count <n>] [--latency]\n  wid convert-file --from sec|ms|ns --to sec|ms|ns [--W <n>] [--Z <n>] < ids\n  wid sort [--kind wid|hlc] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] < ids\n  wid diff <id1> <id2> [--kind wid|hlc] [--W <n>] [--Z <n>] [--time-unit sec|ms|ns] [--json]\n\
+Canonical mode:\n  wid W=# A=# L=# D=# I=# E=# Z=# T=sec|ms|ns R=auto|mqtt|ws|redis|null|stdout N=#\n  wid A=w-otp MODE=gen|verify KEY=<secret|path> [WID=<wid>] [CODE=<otp>] [DIGITS=6] [MAX_AGE_SEC=0|30s|5m|2h] [MAX_FUTURE_SEC=5]\n  wid A=hlc-observe [NODE=<name>] < lines of pt,lc\n  For A=stream: N=0 means infinite stream\n  E supports: state | stateless | sql\n"
     );
 }
 
 fn print_actions() {
     println!(
         "wid action matrix\n\n\
-Core ID:\n  A=next | A=stream | A=healthcheck | A=sign | A=verify | A=w-otp\n\n\
+Core ID:\n  A=next | A=stream | A=healthcheck | A=sign | A=verify | A=w-otp | A=hlc-observe | A=version | A=manifest-discover\n\n\
 Service lifecycle (native):\n  A=discover | A=scaffold | A=run | A=start | A=stop | A=status | A=logs\n\n\
 Service modules (native):\n  A=saf      (alias: raf)\n  A=saf-wid  (aliases: waf, wraf)\n  A=wir      (alias: witr)\n  A=wism     (alias: wim)\n  A=wihp     (alias: wih)\n  A=wipr     (alias: wip)\n  A=duplex\n\n\
 Help:\n  A=help-actions\n\n\
@@ -99,7 +102,26 @@ State mode:\n  E=state | E=stateless | E=sql"
 }
 
 fn parse_time_unit(s: &str) -> Result<TimeUnit, String> {
-    TimeUnit::parse(s).ok_or_else(|| "time-unit must be sec or ms".to_string())
+    TimeUnit::parse(s).ok_or_else(|| "time-unit must be sec, ms or ns".to_string())
+}
+
+/// Parse a duration in seconds: a plain integer (seconds) or an integer with
+/// an `s`, `m` or `h` suffix (`90`, `30s`, `5m`, `2h`).
+fn parse_duration_secs(s: &str) -> Result<u64, String> {
+    let s = s.trim();
+    let (digits, scale) = match s.char_indices().last() {
+        Some((i, 's')) => (&s[..i], 1),
+        Some((i, 'm')) => (&s[..i], 60),
+        Some((i, 'h')) => (&s[..i], 3600),
+        Some((_, c)) if c.is_ascii_digit() => (s, 1),
+        Some((_, c)) => return Err(format!("unknown duration suffix '{c}' (use s, m or h)")),
+        None => return Err("empty duration".to_string()),
+    };
+    let n: u64 = digits
+        .parse()
+        .map_err(|_| format!("invalid duration '{s}'"))?;
+    n.checked_mul(scale)
+        .ok_or_else(|| format!("duration out of range: '{s}'"))
 }
 
 fn parse_validate_flags(args: &[String]) -> Result<ValidateOpts, String> {
@@ -140,6 +162,26 @@ fn parse_validate_flags(args: &[String]) -> Result<ValidateOpts, String> {
                 opts.time_unit = parse_time_unit(&args[i + 1])?;
                 i += 2;
             }
+            "--max-age-sec" => {
+                if i + 1 >= args.len() {
+                    return Err("missing value for --max-age-sec".to_string());
+                }
+                opts.max_age_sec = Some(
+                    parse_duration_secs(&args[i + 1])
+                        .map_err(|e| format!("invalid --max-age-sec: {e}"))?,
+                );
+                i += 2;
+            }
+            "--max-future-sec" => {
+                if i + 1 >= args.len() {
+                    return Err("missing value for --max-future-sec".to_string());
+                }
+                opts.max_future_sec = Some(
+                    parse_duration_secs(&args[i + 1])
+                        .map_err(|e| format!("invalid --max-future-sec: {e}"))?,
+                );
+                i += 2;
+            }
             _ => return Err(format!("unknown flag: {}", args[i])),
         }
     }
@@ -221,55 +263,230 @@ fn parse_emit_flags(args: &[String], allow_count: bool) -> Result<EmitOpts, Stri
     }
 }
 
+/// Render `count` IDs from one generator as a single-line JSON array.
+fn ids_json_array(ids: impl Iterator<Item = String>, count: usize) -> Result<String, String> {
+    let ids: Vec<String> = ids.take(count).collect();
+    serde_json::to_string(&ids).map_err(|e| e.to_string())
+}
+
 fn run_next(args: &[String]) -> Result<(), String> {
-    let opts = parse_emit_flags(args, false)?;
+    let mut json_out = false;
+    let mut tail: Vec<String> = Vec::new();
+    for arg in args {
+        if arg == "--json" {
+            json_out = true;
+        } else {
+            tail.push(arg.clone());
+        }
+    }
 
-    if opts.kind == "wid" {
-        let mut generator = WidGen::new_with_time_unit(opts.w, opts.z, None, opts.time_unit)
-            .map_err(|e| e.to_string())?;
-        println!("{}", generator.next_wid());
+    let opts = parse_emit_flags(&tail, true)?;
+    let count = opts.count.max(1);
+
+    let ids: Box<dyn Iterator<Item = String>> = if opts.kind == "wid" {
+        Box::new(
+            WidGen::new_with_time_unit(opts.w, opts.z, None, opts.time_unit)
+                .map_err(|e| e.to_string())?,
+        )
     } else {
-        let mut generator =
+        Box::new(
             HLCWidGen::new_with_time_unit(opts.node, opts.w, opts.z, opts.time_unit)
-                .map_err(|e| e.to_string())?;
-        println!("{}", generator.next_hlc_wid());
+                .map_err(|e| e.to_string())?,
+        )
+    };
+
+    if json_out {
+        println!("{}", ids_json_array(ids, count)?);
+    } else {
+        for id in ids.take(count) {
+            println!("{id}");
+        }
     }
 
     Ok(())
 }
 
 fn run_stream(args: &[String]) -> Result<(), String> {
-    let opts = parse_emit_flags(args, true)?;
-    let mut emitted = 0usize;
+    let mut buffer = 1usize;
+    let mut tail: Vec<String> = Vec::new();
+    let mut i = 0;
+    while i < args.len() {
+        if args[i] == "--buffer" {
+            let value = args.get(i + 1).ok_or("missing value for --buffer")?;
+            buffer = value
+                .parse::<usize>()
+                .ok()
+                .filter(|n| *n > 0)
+                .ok_or("--buffer must be a positive integer")?;
+            i += 2;
+        } else {
+            tail.push(args[i].clone());
+            i += 1;
+        }
+    }
 
-    if opts.kind == "wid" {
-        let mut generator = WidGen::new_with_time_unit(opts.w, opts.z, None, opts.time_unit)
+    let opts = parse_emit_flags(&tail, true)?;
+    let stream = StreamOpts {
+        count: opts.count,
+        flush_every: buffer,
+        progress_every: 0,
+    };
+    let mut out = BufWriter::new(io::stdout().lock());
+    let written = if opts.kind == "wid" {
+        let ids = WidGen::new_with_time_unit(opts.w, opts.z, None, opts.time_unit)
             .map_err(|e| e.to_string())?;
-        loop {
-            if opts.count > 0 && emitted >= opts.count {
-                break;
-            }
-            println!("{}", generator.next_wid());
-            io::stdout().flush().map_err(|e| e.to_string())?;
-            emitted += 1;
-        }
+        run_stream_with_progress(ids, &stream, &mut out, |_| {})
     } else {
-        let mut generator =
-            HLCWidGen::new_with_time_unit(opts.node, opts.w, opts.z, opts.time_unit)
-                .map_err(|e| e.to_string())?;
-        loop {
-            if opts.count > 0 && emitted >= opts.count {
-                break;
+        let ids = HLCWidGen::new_with_time_unit(opts.node, opts.w, opts.z, opts.time_unit)
+            .map_err(|e| e.to_string())?;
+        run_stream_with_progress(ids, &stream, &mut out, |_| {})
+    };
+    written.map_err(|e| e.to_string())?;
+    Ok(())
+}
+
+#[derive(Debug, Default, PartialEq)]
+struct ConvertStats {
+    converted: usize,
+    lossy: usize,
+    skipped: usize,
+}
+
+fn run_convert_file(args: &[String]) -> Result<(), String> {
+    let mut from = TimeUnit::Sec;
+    let mut to = TimeUnit::Ms;
+    let mut w = 4usize;
+    let mut z = 6usize;
+    let mut i = 0;
+    while i < args.len() {
+        let value = args
+            .get(i + 1)
+            .ok_or(format!("missing value for {}", args[i]));
+        match args[i].as_str() {
+            "--from" => from = parse_time_unit(value?)?,
+            "--to" => to = parse_time_unit(value?)?,
+            "--W" => w = value?.parse().map_err(|_| "invalid --W".to_string())?,
+            "--Z" => z = value?.parse().map_err(|_| "invalid --Z".to_string())?,
+            other => return Err(format!("unknown flag: {other}")),
+        }
+        i += 2;
+    }
+
+    let stats = convert_lines(
+        io::stdin().lock(),
+        &mut io::stdout(),
+        &mut io::stderr(),
+        w,
+        z,
+        from,
+        to,
+    )?;
+    eprintln!(
+        "converted={} lossy={} skipped={}",
+        stats.converted, stats.lossy, stats.skipped
+    );
+    Ok(())
+}
+
+/// Convert one WID per line of `input` with `convert_time_unit`, writing the
+/// results to `out`. Unparseable lines are reported on `warn` and skipped.
+fn convert_lines(
+    input: impl BufRead,
+    out: &mut impl Write,
+    warn: &mut impl Write,
+    w: usize,
+    z: usize,
+    from: TimeUnit,
+    to: TimeUnit,
+) -> Result<ConvertStats, String> {
+    let mut stats = ConvertStats::default();
+    for (lineno, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        match convert_time_unit(line, w, z, from, to) {
+            Ok((converted, lossy)) => {
+                writeln!(out, "{converted}").map_err(|e| e.to_string())?;
+                stats.converted += 1;
+                stats.lossy += usize::from(lossy);
+            }
+            Err(e) => {
+                writeln!(warn, "warning: line {}: {e}", lineno + 1).map_err(|e| e.to_string())?;
+                stats.skipped += 1;
             }
-            println!("{}", generator.next_hlc_wid());
-            io::stdout().flush().map_err(|e| e.to_string())?;
-            emitted += 1;
         }
     }
+    out.flush().map_err(|e| e.to_string())?;
+    Ok(stats)
+}
 
+fn run_sort(args: &[String]) -> Result<(), String> {
+    let opts = parse_validate_flags(args)?;
+    let failed = sort_lines(
+        io::stdin().lock(),
+        &mut io::stdout(),
+        &mut io::stderr(),
+        &opts,
+    )?;
+    if failed > 0 {
+        return Err(format!("{failed} line(s) could not be parsed"));
+    }
     Ok(())
 }
 
+/// Write the IDs on `input` (one per line) to `out` in `ParsedWid` or
+/// `ParsedHlcWid` order. Unparseable lines are reported on `warn` after the
+/// sorted output; returns how many there were.
+fn sort_lines(
+    input: impl BufRead,
+    out: &mut impl Write,
+    warn: &mut impl Write,
+    opts: &ValidateOpts,
+) -> Result<usize, String> {
+    let mut failures = Vec::new();
+    let mut lines = Vec::new();
+    for (lineno, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if !line.is_empty() {
+            lines.push((lineno + 1, line.to_string()));
+        }
+    }
+
+    let sorted: Vec<String> = if opts.kind == "wid" {
+        let mut parsed = Vec::with_capacity(lines.len());
+        for (lineno, line) in &lines {
+            match parse_wid_with_unit(line, opts.w, opts.z, opts.time_unit) {
+                Ok(wid) => parsed.push(wid),
+                Err(e) => failures.push(format!("line {lineno}: {e}")),
+            }
+        }
+        parsed.sort();
+        parsed.into_iter().map(|wid| wid.raw).collect()
+    } else {
+        let mut parsed = Vec::with_capacity(lines.len());
+        for (lineno, line) in &lines {
+            match parse_hlc_wid_with_unit(line, opts.w, opts.z, opts.time_unit) {
+                Ok(wid) => parsed.push(wid),
+                Err(e) => failures.push(format!("line {lineno}: {e}")),
+            }
+        }
+        parsed.sort();
+        parsed.into_iter().map(|wid| wid.raw).collect()
+    };
+
+    for id in sorted {
+        writeln!(out, "{id}").map_err(|e| e.to_string())?;
+    }
+    out.flush().map_err(|e| e.to_string())?;
+    for failure in &failures {
+        writeln!(warn, "warning: {failure}").map_err(|e| e.to_string())?;
+    }
+    Ok(failures.len())
+}
+
 fn run_healthcheck(args: &[String]) -> Result<(), String> {
     let mut json_mode = false;
     let mut tail: Vec<String> = Vec::new();
@@ -360,12 +577,7 @@ fn run_validate(args: &[String]) -> Result<(), String> {
 
     let id = args[0].clone();
     let opts = parse_validate_flags(&args[1..])?;
-
-    let ok = if opts.kind == "wid" {
-        validate_wid_with_unit(&id, opts.w, opts.z, opts.time_unit)
-    } else {
-        validate_hlc_wid_with_unit(&id, opts.w, opts.z, opts.time_unit)
-    };
+    let ok = validate_with_opts(&id, &opts);
 
     println!("{}", if ok { "true" } else { "false" });
     if ok {
@@ -375,6 +587,150 @@ fn run_validate(args: &[String]) -> Result<(), String> {
     }
 }
 
+/// Format check, plus the time-window check when either window flag is set.
+fn validate_with_opts(id: &str, opts: &ValidateOpts) -> bool {
+    let is_wid = opts.kind == "wid";
+    if opts.max_age_sec.is_none() && opts.max_future_sec.is_none() {
+        return if is_wid {
+            validate_wid_with_unit(id, opts.w, opts.z, opts.time_unit)
+        } else {
+            validate_hlc_wid_with_unit(id, opts.w, opts.z, opts.time_unit)
+        };
+    }
+    let check = if is_wid {
+        validate_wid_in_window
+    } else {
+        validate_hlc_wid_in_window
+    };
+    check(
+        id,
+        opts.w,
+        opts.z,
+        opts.time_unit,
+        opts.max_age_sec.map_or(Duration::MAX, Duration::from_secs),
+        Duration::from_secs(opts.max_future_sec.unwrap_or(5)),
+    )
+}
+
+fn run_diff(args: &[String]) -> Result<(), String> {
+    if args.len() < 2 {
+        return Err("diff requires two ids".to_string());
+    }
+
+    let mut json_out = false;
+    let mut tail: Vec<String> = Vec::new();
+    for arg in &args[2..] {
+        if arg == "--json" {
+            json_out = true;
+        } else {
+            tail.push(arg.clone());
+        }
+    }
+
+    let opts = parse_validate_flags(&tail)?;
+    let diff = diff_ids(&args[0], &args[1], &opts)?;
+    if json_out {
+        println!(
+            "{}",
+            serde_json::to_string(&diff).map_err(|e| e.to_string())?
+        );
+    } else {
+        println!("{}", format_diff(&diff));
+    }
+    Ok(())
+}
+
+/// Time from `a` to `b` in `opts.time_unit` ticks, plus the sequence delta
+/// for WIDs sharing a timestamp (`null` otherwise) or, for HLC-WIDs, the
+/// logical-counter delta and whether both came from the same node.
+fn diff_ids(a: &str, b: &str, opts: &ValidateOpts) -> Result<serde_json::Value, String> {
+    let unit = opts.time_unit;
+    let delta_of = |d: chrono::Duration| -> Result<i64, String> {
+        match unit {
+            TimeUnit::Sec => Some(d.num_seconds()),
+            TimeUnit::Ms => Some(d.num_milliseconds()),
+            TimeUnit::Ns => d.num_nanoseconds(),
+        }
+        .ok_or_else(|| "delta out of range".to_string())
+    };
+
+    if opts.kind == "wid" {
+        let a = parse_wid_with_unit(a, opts.w, opts.z, unit).map_err(|e| e.to_string())?;
+        let b = parse_wid_with_unit(b, opts.w, opts.z, unit).map_err(|e| e.to_string())?;
+        let delta = b.timestamp - a.timestamp;
+        let sequence_delta = delta
+            .is_zero()
+            .then(|| b.sequence as i128 - a.sequence as i128);
+        Ok(json!({
+            "kind": "wid",
+            "unit": unit.as_str(),
+            "delta": delta_of(delta)?,
+            "delta_secs": delta.num_seconds(),
+            "sequence_delta": sequence_delta,
+        }))
+    } else {
+        let a = parse_hlc_wid_with_unit(a, opts.w, opts.z, unit).map_err(|e| e.to_string())?;
+        let b = parse_hlc_wid_with_unit(b, opts.w, opts.z, unit).map_err(|e| e.to_string())?;
+        let delta = b.timestamp - a.timestamp;
+        Ok(json!({
+            "kind": "hlc",
+            "unit": unit.as_str(),
+            "delta": delta_of(delta)?,
+            "delta_secs": delta.num_seconds(),
+            "logical_counter_delta": b.logical_counter as i128 - a.logical_counter as i128,
+            "same_node": a.node == b.node,
+        }))
+    }
+}
+
+/// One-line rendering of `diff_ids`, e.g.
+/// `delta=+2550s (42m 30s) sequence_delta=N/A`. The breakdown in
+/// parentheses only appears for gaps of a minute or more.
+fn format_diff(diff: &serde_json::Value) -> String {
+    let unit = match diff["unit"].as_str() {
+        Some("sec") => "s",
+        Some(unit) => unit,
+        None => "",
+    };
+    let mut line = format!("delta={:+}{unit}", diff["delta"].as_i64().unwrap_or(0));
+    let secs = diff["delta_secs"].as_i64().unwrap_or(0).unsigned_abs();
+    if secs >= 60 {
+        line.push_str(&format!(" ({})", format_span(secs)));
+    }
+    if diff["kind"] == "wid" {
+        match diff["sequence_delta"].as_i64() {
+            Some(d) => line.push_str(&format!(" sequence_delta={d:+}")),
+            None => line.push_str(" sequence_delta=N/A"),
+        }
+    } else {
+        line.push_str(&format!(
+            " logical_counter_delta={:+} same_node={}",
+            diff["logical_counter_delta"].as_i64().unwrap_or(0),
+            diff["same_node"]
+        ));
+    }
+    line
+}
+
+/// `secs` as `1d 2h 3m 4s`, omitting zero components.
+fn format_span(secs: u64) -> String {
+    let parts: Vec<String> = [
+        (secs / 86_400, "d"),
+        (secs % 86_400 / 3600, "h"),
+        (secs % 3600 / 60, "m"),
+        (secs % 60, "s"),
+    ]
+    .iter()
+    .filter(|(n, _)| *n > 0)
+    .map(|(n, suffix)| format!("{n}{suffix}"))
+    .collect();
+    if parts.is_empty() {
+        "0s".to_string()
+    } else {
+        parts.join(" ")
+    }
+}
+
 fn run_parse(args: &[String]) -> Result<(), String> {
     if args.is_empty() {
         return Err("parse requires an id".to_string());
@@ -441,33 +797,106 @@ fn run_parse(args: &[String]) -> Result<(), String> {
     Ok(())
 }
 
+/// Bounded uniform sample of per-call latencies (Vitter's algorithm R), so
+/// `--latency` memory stays fixed regardless of `--count`. `max` is exact.
+struct LatencyReservoir {
+    samples: Vec<u64>,
+    capacity: usize,
+    seen: u64,
+    max: u64,
+}
+
+impl LatencyReservoir {
+    fn new(capacity: usize) -> Self {
+        Self {
+            samples: Vec::with_capacity(capacity),
+            capacity,
+            seen: 0,
+            max: 0,
+        }
+    }
+
+    fn record(&mut self, ns: u64) {
+        self.seen += 1;
+        self.max = self.max.max(ns);
+        if self.samples.len() < self.capacity {
+            self.samples.push(ns);
+        } else {
+            let slot = random_range(0..self.seen);
+            if slot < self.capacity as u64 {
+                self.samples[slot as usize] = ns;
+            }
+        }
+    }
+}
+
+/// Nearest-rank percentile of an ascending slice; 0 when empty.
+fn percentile_ns(sorted: &[u64], p: f64) -> u64 {
+    if sorted.is_empty() {
+        return 0;
+    }
+    let rank = ((p / 100.0) * sorted.len() as f64).ceil() as usize;
+    sorted[rank.clamp(1, sorted.len()) - 1]
+}
+
+/// Call `next` `count` times, timing each call into `reservoir` when
+/// `latency` is set. Generic so each generator gets its own statically
+/// dispatched loop.
+fn bench_calls(
+    count: usize,
+    latency: bool,
+    reservoir: &mut LatencyReservoir,
+    mut next: impl FnMut() -> String,
+) {
+    if latency {
+        for _ in 0..count {
+            let t = Instant::now();
+            let _ = next();
+            reservoir.record(t.elapsed().as_nanos() as u64);
+        }
+    } else {
+        for _ in 0..count {
+            let _ = next();
+        }
+    }
+}
+
 fn run_bench(args: &[String]) -> Result<(), String> {
-    let mut opts = parse_emit_flags(args, true)?;
+    let mut latency = false;
+    let mut tail: Vec<String> = Vec::new();
+    for arg in args {
+        if arg == "--latency" {
+            latency = true;
+        } else {
+            tail.push(arg.clone());
+        }
+    }
+
+    let mut opts = parse_emit_flags(&tail, true)?;
     if opts.count == 0 {
         opts.count = 100_000;
     }
 
+    let mut reservoir = LatencyReservoir::new(if latency { 10_000 } else { 0 });
     let start = Instant::now();
 
     if opts.kind == "wid" {
         let mut generator = WidGen::new_with_time_unit(opts.w, opts.z, None, opts.time_unit)
             .map_err(|e| e.to_string())?;
-        for _ in 0..opts.count {
-            let _ = generator.next_wid();
-        }
+        bench_calls(opts.count, latency, &mut reservoir, || generator.next_wid());
     } else {
         let mut generator =
             HLCWidGen::new_with_time_unit(opts.node.clone(), opts.w, opts.z, opts.time_unit)
                 .map_err(|e| e.to_string())?;
-        for _ in 0..opts.count {
-            let _ = generator.next_hlc_wid();
-        }
+        bench_calls(opts.count, latency, &mut reservoir, || {
+            generator.next_hlc_wid()
+        });
     }
 
     let secs = start.elapsed().as_secs_f64().max(1e-9);
     let ips = opts.count as f64 / secs;
 
-    let payload = json!({
+    let mut payload = json!({
         "impl": "rust",
         "kind": opts.kind,
         "W": opts.w,
@@ -477,6 +906,12 @@ fn run_bench(args: &[String]) -> Result<(), String> {
         "seconds": secs,
         "ids_per_sec": ips,
     });
+    if latency {
+        reservoir.samples.sort_unstable();
+        payload["p50_ns"] = json!(percentile_ns(&reservoir.samples, 50.0));
+        payload["p99_ns"] = json!(percentile_ns(&reservoir.samples, 99.0));
+        payload["max_ns"] = json!(reservoir.max);
+    }
     println!(
         "{}",
         serde_json::to_string(&payload).map_err(|e| e.to_string())?
@@ -673,16 +1108,103 @@ fn run_service_action(c: &CanonOpts, action: &str) -> Result<(), String> {
     Ok(())
 }
 
-fn run_discover() -> Result<(), String> {
-    let payload = json!({
+/// Optional Cargo features compiled into this binary, one flag per feature
+/// declared in `Cargo.toml`.
+fn discover_features() -> serde_json::Value {
+    json!({
+        "crossbeam": cfg!(feature = "crossbeam"),
+        "serde": cfg!(feature = "serde"),
+    })
+}
+
+/// Actions handled by the native orchestrator, as listed by `discover`.
+const ORCHESTRATION_ACTIONS: &[&str] = &[
+    "discover", "scaffold", "run", "start", "stop", "status", "logs", "saf", "saf-wid", "wir",
+    "wism", "wihp", "wipr", "duplex",
+];
+
+fn discover_payload() -> serde_json::Value {
+    json!({
         "impl":"rust",
         "orchestration":"native",
-        "actions":["discover","scaffold","run","start","stop","status","logs","saf","saf-wid","wir","wism","wihp","wipr","duplex"],
-        "transports":["auto","mqtt","ws","redis","null","stdout"]
-    });
+        "actions":ORCHESTRATION_ACTIONS,
+        "transports":["auto","mqtt","ws","redis","null","stdout"],
+        "features":discover_features()
+    })
+}
+
+fn run_discover() -> Result<(), String> {
     println!(
         "{}",
-        serde_json::to_string(&payload).map_err(|e| e.to_string())?
+        serde_json::to_string(&discover_payload()).map_err(|e| e.to_string())?
+    );
+    Ok(())
+}
+
+/// Versions this binary implements, for orchestrators negotiating
+/// compatibility before exchanging IDs or SYNAPSE files.
+fn version_payload() -> serde_json::Value {
+    json!({
+        "crate_version": env!("CARGO_PKG_VERSION"),
+        "manifest_version": MANIFEST_VERSION,
+        "wid_format": WID_FORMAT,
+        "time_units": [
+            TimeUnit::Sec.as_str(),
+            TimeUnit::Ms.as_str(),
+            TimeUnit::Ns.as_str(),
+        ],
+    })
+}
+
+fn run_version() -> Result<(), String> {
+    println!(
+        "{}",
+        serde_json::to_string(&version_payload()).map_err(|e| e.to_string())?
+    );
+    Ok(())
+}
+
+/// Bumped whenever a section of `manifest_discover_payload` changes shape.
+const DISCOVERY_SCHEMA_VERSION: u32 = 1;
+
+/// Canonical actions handled before orchestration dispatch.
+const CORE_ACTIONS: &[&str] = &[
+    "next",
+    "stream",
+    "healthcheck",
+    "sign",
+    "verify",
+    "w-otp",
+    "hlc-observe",
+    "version",
+    "manifest-discover",
+    "help-actions",
+];
+
+/// `discover`, `version` and the feature probe in one document, so an
+/// orchestrator can describe a wid process with a single call. `actions`
+/// lists core actions first, then the orchestration ones from `discover`.
+fn manifest_discover_payload() -> serde_json::Value {
+    let discover = discover_payload();
+    let mut actions: Vec<serde_json::Value> = CORE_ACTIONS.iter().map(|a| json!(a)).collect();
+    if let Some(native) = discover["actions"].as_array() {
+        actions.extend(native.iter().cloned());
+    }
+    json!({
+        "schema_version": DISCOVERY_SCHEMA_VERSION,
+        "impl": discover["impl"],
+        "orchestration": discover["orchestration"],
+        "actions": actions,
+        "transports": discover["transports"],
+        "features": discover["features"],
+        "version": version_payload(),
+    })
+}
+
+fn run_manifest_discover() -> Result<(), String> {
+    println!(
+        "{}",
+        serde_json::to_string(&manifest_discover_payload()).map_err(|e| e.to_string())?
     );
     Ok(())
 }
@@ -861,6 +1383,7 @@ fn parse_canonical(args: &[String]) -> Result<CanonOpts, String> {
         digits: 6,
         max_age_sec: 0,
         max_future_sec: 5,
+        node: default_node(),
     };
 
     for arg in args {
@@ -910,12 +1433,15 @@ fn parse_canonical(args: &[String]) -> Result<CanonOpts, String> {
             "OUT" => o.out = v.to_string(),
             "MODE" => o.mode = v.to_string(),
             "CODE" => o.code = v.to_string(),
+            "NODE" => o.node = v.to_string(),
             "DIGITS" => o.digits = v.parse().map_err(|_| "invalid DIGITS".to_string())?,
             "MAX_AGE_SEC" => {
-                o.max_age_sec = v.parse().map_err(|_| "invalid MAX_AGE_SEC".to_string())?
+                o.max_age_sec =
+                    parse_duration_secs(v).map_err(|e| format!("invalid MAX_AGE_SEC: {e}"))?
             }
             "MAX_FUTURE_SEC" => {
-                o.max_future_sec = v.parse().map_err(|_| "invalid MAX_FUTURE_SEC".to_string())?
+                o.max_future_sec =
+                    parse_duration_secs(v).map_err(|e| format!("invalid MAX_FUTURE_SEC: {e}"))?
             }
             _ => return Err(format!("unknown key: {k}")),
         }
@@ -963,6 +1489,15 @@ fn run_canonical(args: &[String]) -> Result<(), String> {
     if c.a == "w-otp" {
         return run_wotp(&c);
     }
+    if c.a == "hlc-observe" {
+        return run_hlc_observe(&c);
+    }
+    if c.a == "version" {
+        return run_version();
+    }
+    if c.a == "manifest-discover" {
+        return run_manifest_discover();
+    }
 
     let (state_mode, _) = parse_state_and_transport(&c);
     if state_mode == "sql" && (c.a == "next" || c.a == "stream") {
@@ -1004,38 +1539,64 @@ fn run_canonical(args: &[String]) -> Result<(), String> {
     }
 }
 
-/// Build the canonical sign/verify message entirely in memory:
-/// `"wid-sig-v1:" || len(WID) || ":" || WID || DATA`.
-///
-/// The domain-separation prefix and the explicit WID byte-length frame the
-/// WID/DATA boundary so no bytes can shift between them (a plain `WID || DATA`
-/// concatenation is ambiguous). No temporary files are created.
-fn build_sign_verify_message(c: &CanonOpts) -> Result<Vec<u8>, String> {
-    if c.wid.trim().is_empty() {
-        return Err("WID=<wid_string> required".to_string());
-    }
-    let wid = c.wid.as_bytes();
-    let mut msg = format!("wid-sig-v1:{}:", wid.len()).into_bytes();
-    msg.extend_from_slice(wid);
-    if !c.data.trim().is_empty() {
-        let data = fs::read(&c.data).map_err(|_| format!("data file not found: {}", c.data))?;
-        msg.extend_from_slice(&data);
-    }
-    Ok(msg)
+fn run_hlc_observe(c: &CanonOpts) -> Result<(), String> {
+    let mut generator =
+        HLCWidGen::new_with_time_unit(c.node.clone(), c.w, c.z, c.t).map_err(|e| e.to_string())?;
+    hlc_observe_loop(
+        &mut generator,
+        io::stdin().lock(),
+        &mut io::stdout(),
+        &mut io::stderr(),
+    )?;
+    Ok(())
 }
 
-fn load_signing_key(path: &str) -> Result<SigningKey, String> {
-    let pem =
-        fs::read_to_string(path).map_err(|_| format!("private key file not found: {path}"))?;
-    SigningKey::from_pkcs8_pem(&pem)
-        .map_err(|_| "sign failed (ensure Ed25519 private key PEM)".to_string())
+/// Merge each `pt,lc` line of `input` into `generator` and write the local
+/// HLC-WID minted right after it. Malformed lines and rejected remote clocks
+/// are reported on `warn` and skipped. Returns the number of IDs written.
+fn hlc_observe_loop(
+    generator: &mut HLCWidGen,
+    input: impl BufRead,
+    out: &mut impl Write,
+    warn: &mut impl Write,
+) -> Result<usize, String> {
+    let mut emitted = 0usize;
+    for (lineno, line) in input.lines().enumerate() {
+        let line = line.map_err(|e| e.to_string())?;
+        let line = line.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let remote = line
+            .split_once(',')
+            .and_then(|(pt, lc)| Some((pt.trim().parse().ok()?, lc.trim().parse().ok()?)));
+        let observed = match remote {
+            Some((pt, lc)) => generator.observe(pt, lc).map_err(|e| e.to_string()),
+            None => Err("expected pt,lc".to_string()),
+        };
+        if let Err(e) = observed {
+            writeln!(warn, "warning: line {}: {e}: '{line}'", lineno + 1)
+                .map_err(|e| e.to_string())?;
+            continue;
+        }
+        writeln!(out, "{}", generator.next_hlc_wid()).map_err(|e| e.to_string())?;
+        out.flush().map_err(|e| e.to_string())?;
+        emitted += 1;
+    }
+    Ok(emitted)
 }
 
-fn load_verifying_key(path: &str) -> Result<VerifyingKey, String> {
-    let pem =
-        fs::read_to_string(path).map_err(|_| format!("public key file not found: {path}"))?;
-    VerifyingKey::from_public_key_pem(&pem)
-        .map_err(|_| "invalid public key (ensure Ed25519 public key PEM)".to_string())
+/// Read the optional `DATA=<path>` payload that is signed after the WID.
+fn read_sign_data(c: &CanonOpts) -> Result<Option<Vec<u8>>, String> {
+    if c.wid.trim().is_empty() {
+        return Err("WID=<wid_string> required".to_string());
+    }
+    if c.data.trim().is_empty() {
+        return Ok(None);
+    }
+    fs::read(&c.data)
+        .map(Some)
+        .map_err(|_| format!("data file not found: {}", c.data))
 }
 
 fn run_sign(c: &CanonOpts) -> Result<(), String> {
@@ -1045,10 +1606,10 @@ fn run_sign(c: &CanonOpts) -> Result<(), String> {
     if !Path::new(&c.key).exists() {
         return Err(format!("private key file not found: {}", c.key));
     }
-    let msg = build_sign_verify_message(c)?;
-    let key = load_signing_key(&c.key)?;
-    let sig: Signature = key.sign(&msg);
-    let encoded = URL_SAFE_NO_PAD.encode(sig.to_bytes());
+    let data = read_sign_data(c)?;
+    let pem = fs::read(&c.key).map_err(|_| format!("private key file not found: {}", c.key))?;
+    let encoded = sign_wid(&c.wid, data.as_deref(), &pem)
+        .map_err(|_| "sign failed (ensure Ed25519 private key PEM)".to_string())?;
     if c.out.trim().is_empty() {
         println!("{encoded}");
     } else {
@@ -1068,20 +1629,18 @@ fn run_verify(c: &CanonOpts) -> Result<(), String> {
     if !Path::new(&c.key).exists() {
         return Err(format!("public key file not found: {}", c.key));
     }
-    let msg = build_sign_verify_message(c)?;
-    let key = load_verifying_key(&c.key)?;
-    // Accept base64url with or without padding.
-    let sig_bytes = URL_SAFE_NO_PAD
-        .decode(c.sig.trim().trim_end_matches('='))
-        .map_err(|_| "invalid signature encoding".to_string())?;
-    let sig =
-        Signature::from_slice(&sig_bytes).map_err(|_| "invalid signature encoding".to_string())?;
-    match key.verify_strict(&msg, &sig) {
-        Ok(()) => {
+    let data = read_sign_data(c)?;
+    let pem = fs::read(&c.key).map_err(|_| format!("public key file not found: {}", c.key))?;
+    match verify_signed(&c.wid, data.as_deref(), &c.sig, &pem) {
+        Ok(true) => {
             println!("Signature valid.");
             Ok(())
         }
-        Err(_) => Err("Signature invalid.".to_string()),
+        Ok(false) => Err("Signature invalid.".to_string()),
+        Err(WidError::InvalidKey) => {
+            Err("invalid public key (ensure Ed25519 public key PEM)".to_string())
+        }
+        Err(_) => Err("invalid signature encoding".to_string()),
     }
 }
 
@@ -1098,34 +1657,18 @@ fn resolve_wotp_secret(raw: &str) -> Result<String, String> {
     Ok(trimmed.to_string())
 }
 
-fn compute_wotp(secret: &str, wid: &str, digits: usize) -> Result<String, String> {
-    let mut mac = HmacSha256::new_from_slice(secret.as_bytes())
-        .map_err(|_| "failed to initialize HMAC".to_string())?;
-    mac.update(wid.as_bytes());
-    let digest = mac.finalize().into_bytes();
-    if digest.len() < 4 {
-        return Err("failed to compute w-otp digest".to_string());
-    }
-    let v = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
-    let mut m = 1u32;
-    for _ in 0..digits {
-        m = m.saturating_mul(10);
-    }
-    Ok(format!("{:0width$}", v % m, width = digits))
-}
-
-/// Extract epoch-milliseconds from the leading timestamp of a WID, for the
-/// w-otp time-window (freshness) check only. This is deliberately lenient and
-/// independent of `W`/`Z` and of whether the WID is plain or HLC: the timestamp
-/// prefix is always `YYYYMMDDThhmmss` (seconds) or `YYYYMMDDThhmmssSSS`
-/// (milliseconds). Using the strict WID parser here made `verify` reject WIDs
-/// that `gen` had just accepted, and disagreed with the other language
-/// implementations (which all use this same lenient extraction).
-fn wotp_wid_tick_ms(wid: &str) -> Result<i64, String> {
+/// Age of a WID from its leading timestamp, for the w-otp time-window
+/// (freshness) check only. This is deliberately lenient and independent of
+/// `W`/`Z` and of whether the WID is plain or HLC: the timestamp prefix is
+/// always `YYYYMMDDThhmmss` followed by 0 (seconds), 3 (milliseconds) or 9
+/// (nanoseconds) fraction digits. Using the strict WID parser here made
+/// `verify` reject WIDs that `gen` had just accepted, and disagreed with the
+/// other language implementations (which all use this same lenient extraction).
+fn wotp_wid_age(wid: &str) -> Result<chrono::Duration, String> {
     let err = || "WID timestamp is invalid for time-window verification".to_string();
     let ts = wid.split('.').next().unwrap_or("");
     let (date, time) = ts.split_once('T').ok_or_else(err)?;
-    if date.len() != 8 || !(time.len() == 6 || time.len() == 9) {
+    if date.len() != 8 || ![6, 9, 15].contains(&time.len()) {
         return Err(err());
     }
     if !date.bytes().all(|b| b.is_ascii_digit()) || !time.bytes().all(|b| b.is_ascii_digit()) {
@@ -1133,18 +1676,22 @@ fn wotp_wid_tick_ms(wid: &str) -> Result<i64, String> {
     }
     let num = |s: &str| s.parse::<u32>().map_err(|_| err());
     let year: i32 = date[0..4].parse().map_err(|_| err())?;
-    let month = num(&date[4..6])?;
-    let day = num(&date[6..8])?;
-    let hour = num(&time[0..2])?;
-    let minute = num(&time[2..4])?;
-    let second = num(&time[4..6])?;
-    let millis: i64 = if time.len() == 9 { time[6..9].parse().map_err(|_| err())? } else { 0 };
+    let fraction = &time[6..];
+    let nanos = num(&format!("{fraction:0<9}"))?;
     use chrono::TimeZone;
     let dt = chrono::Utc
-        .with_ymd_and_hms(year, month, day, hour, minute, second)
+        .with_ymd_and_hms(
+            year,
+            num(&date[4..6])?,
+            num(&date[6..8])?,
+            num(&time[0..2])?,
+            num(&time[2..4])?,
+            num(&time[4..6])?,
+        )
         .single()
-        .ok_or_else(err)?;
-    Ok(dt.timestamp_millis() + millis)
+        .ok_or_else(err)?
+        + chrono::Duration::nanoseconds(i64::from(nanos));
+    Ok(chrono::Utc::now().signed_duration_since(dt))
 }
 
 fn run_wotp(c: &CanonOpts) -> Result<(), String> {
@@ -1172,7 +1719,7 @@ fn run_wotp(c: &CanonOpts) -> Result<(), String> {
     if wid.trim().is_empty() {
         return Err("WID=<wid_string> required for A=w-otp MODE=verify".to_string());
     }
-    let otp = compute_wotp(&secret, &wid, c.digits)?;
+    let otp = compute_wotp(&secret, &wid, c.digits).map_err(|e| e.to_string())?;
     if mode == "gen" {
         println!("{}", json!({"wid": wid, "otp": otp, "digits": c.digits}));
         return Ok(());
@@ -1181,14 +1728,15 @@ fn run_wotp(c: &CanonOpts) -> Result<(), String> {
         return Err("CODE=<otp_code> required for A=w-otp MODE=verify".to_string());
     }
     if c.max_age_sec > 0 || c.max_future_sec > 0 {
-        let wid_ms = wotp_wid_tick_ms(&wid)?;
-        let now_ms = chrono::Utc::now().timestamp_millis();
-        let delta = now_ms - wid_ms;
-        if delta < 0 {
-            if -delta > (c.max_future_sec as i64) * 1000 {
-                return Err("OTP invalid: WID timestamp is too far in the future".to_string());
-            }
-        } else if c.max_age_sec > 0 && delta > (c.max_age_sec as i64) * 1000 {
+        // Only the timestamp matters here, so W, Z and the time unit of the
+        // verifying invocation need not match the ones the WID was minted with.
+        // Clamp to chrono's range so huge limits mean "unbounded" rather than panic.
+        let secs = |s: u64| chrono::Duration::seconds(s.min(i64::MAX as u64 / 1000) as i64);
+        let age = wotp_wid_age(&wid)?;
+        if -age > secs(c.max_future_sec) {
+            return Err("OTP invalid: WID timestamp is too far in the future".to_string());
+        }
+        if c.max_age_sec > 0 && age > secs(c.max_age_sec) {
             return Err("OTP invalid: WID timestamp is too old".to_string());
         }
     }
@@ -1296,17 +1844,30 @@ fn run_canonical_sql_stream(c: &CanonOpts) -> Result<(), String> {
     Ok(())
 }
 
-fn print_completion(shell: &str) {
-    match shell {
-        "bash" => print!(
+/// Completion script for `shell`, or `None` if it is not supported.
+fn completion_script(shell: &str) -> Option<String> {
+    // Every `A=` value, so the scripts cannot drift from the dispatcher.
+    let actions = CORE_ACTIONS
+        .iter()
+        .chain(ORCHESTRATION_ACTIONS)
+        .copied()
+        .collect::<Vec<_>>();
+    let fish_actions = actions
+        .iter()
+        .map(|a| format!("A={a}"))
+        .collect::<Vec<_>>()
+        .join(" ");
+    let actions = actions.join(" ");
+    let script = match shell {
+        "bash" => format!(
             r#"_wid_complete() {{
   local cur="${{COMP_WORDS[COMP_CWORD]}}"
-  local cmds="next stream healthcheck validate parse help-actions bench selftest completion"
+  local cmds="next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff"
   if [[ "$cur" == *=* ]]; then
     local key="${{cur%%=*}}" val="${{cur#*=}}" vals=""
     case "$key" in
-      A) vals="next stream healthcheck sign verify w-otp discover scaffold run start stop status logs saf saf-wid wir wism wihp wipr duplex help-actions" ;;
-      T) vals="sec ms" ;;
+      A) vals="{actions}" ;;
+      T) vals="sec ms ns" ;;
       I) vals="auto sh bash" ;;
       E) vals="state stateless sql" ;;
       R) vals="auto mqtt ws redis null stdout" ;;
@@ -1322,17 +1883,17 @@ fn print_completion(shell: &str) {
 complete -o nospace -F _wid_complete wid
 "#
         ),
-        "zsh" => print!(
+        "zsh" => format!(
             r#"#compdef wid
 _wid_complete() {{
   local cur="${{words[-1]}}"
-  local -a cmds=(next stream healthcheck validate parse help-actions bench selftest completion)
+  local -a cmds=(next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff)
   if [[ "$cur" == *=* ]]; then
     local key="${{cur%%=*}}"
     local -a vals=()
     case "$key" in
-      A) vals=(next stream healthcheck sign verify w-otp discover scaffold run start stop status logs saf saf-wid wir wism wihp wipr duplex help-actions) ;;
-      T) vals=(sec ms) ;;
+      A) vals=({actions}) ;;
+      T) vals=(sec ms ns) ;;
       I) vals=(auto sh bash) ;;
       E) vals=(state stateless sql) ;;
       R) vals=(auto mqtt ws redis null stdout) ;;
@@ -1346,17 +1907,20 @@ _wid_complete() {{
 _wid_complete "$@"
 "#
         ),
-        "fish" => print!(
+        "fish" => format!(
             r#"complete -c wid -e
-complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion' -a next -d 'Emit one WID'
-complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion' -a stream -d 'Stream WIDs continuously'
-complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion' -a healthcheck -d 'Generate and validate a sample WID'
-complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion' -a validate -d 'Validate a WID string'
-complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion' -a parse -d 'Parse a WID string'
-complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion' -a help-actions -d 'Show canonical action matrix'
-complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion' -a completion -d 'Print shell completion script'
-complete -c wid -f -a 'A=next A=stream A=healthcheck A=sign A=verify A=w-otp A=start A=stop A=status A=logs A=help-actions' -d 'Action'
-complete -c wid -f -a 'T=sec T=ms' -d 'Time unit'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a next -d 'Emit one WID'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a stream -d 'Stream WIDs continuously'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a healthcheck -d 'Generate and validate a sample WID'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a validate -d 'Validate a WID string'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a parse -d 'Parse a WID string'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a help-actions -d 'Show canonical action matrix'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a completion -d 'Print shell completion script'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a convert-file -d 'Convert WIDs between time units'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a sort -d 'Sort IDs chronologically'
+complete -c wid -f -n 'not __fish_seen_subcommand_from next stream healthcheck validate parse help-actions bench selftest completion convert-file sort diff' -a diff -d 'Compare two IDs'
+complete -c wid -f -a '{fish_actions}' -d 'Action'
+complete -c wid -f -a 'T=sec T=ms T=ns' -d 'Time unit'
 complete -c wid -f -a 'I=auto I=sh I=bash' -d 'Input source'
 complete -c wid -f -a 'E=state E=stateless E=sql' -d 'State mode'
 complete -c wid -f -a 'R=auto R=mqtt R=ws R=redis R=null R=stdout' -d 'Transport'
@@ -1367,11 +1931,9 @@ complete -c wid -f -a 'N=' -d 'Count'
 complete -c wid -f -a 'L=' -d 'Interval seconds'
 "#
         ),
-        _ => {
-            eprintln!("error: unknown shell '{shell}'. Use: wid completion bash|zsh|fish");
-            process::exit(1);
-        }
-    }
+        _ => return None,
+    };
+    Some(script)
 }
 
 fn main() {
@@ -1418,7 +1980,13 @@ fn main() {
             eprintln!("usage: wid completion bash|zsh|fish");
             process::exit(1);
         }
-        print_completion(shell);
+        match completion_script(shell) {
+            Some(script) => print!("{script}"),
+            None => {
+                eprintln!("error: unknown shell '{shell}'. Use: wid completion bash|zsh|fish");
+                process::exit(1);
+            }
+        }
         return;
     }
 
@@ -1429,6 +1997,9 @@ fn main() {
         "validate" => run_validate(rest),
         "parse" => run_parse(rest),
         "bench" => run_bench(rest),
+        "convert-file" => run_convert_file(rest),
+        "sort" => run_sort(rest),
+        "diff" => run_diff(rest),
         "selftest" => match WidGen::new_with_time_unit(4, 0, None, TimeUnit::Sec) {
             Ok(mut g) => {
                 let a = g.next_wid();
@@ -1454,10 +2025,57 @@ fn main() {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_wotp_wid_age_ignores_w_z_and_kind() {
+        let year = chrono::Duration::days(365);
+        for wid in [
+            "20200101T000000.0Z",
+            "20200101T000000.000042Z-node01-a3f91c",
+            "20200101T000000123.0000Z",
+            "20200101T000000123456789.00Z-ab",
+        ] {
+            assert!(wotp_wid_age(wid).unwrap() > year, "{wid}");
+        }
+        let ms = wotp_wid_age("20200101T000000500.0Z").unwrap();
+        let sec = wotp_wid_age("20200101T000000.0Z").unwrap();
+        assert!(sec - ms >= chrono::Duration::milliseconds(499));
+        for bad in ["not-a-wid", "20201301T000000.0Z", "20200101T0000.0Z"] {
+            assert!(wotp_wid_age(bad).is_err(), "{bad}");
+        }
+    }
+
+    #[test]
+    fn test_wotp_verify_window_ignores_wid_shape() {
+        let fresh_ms = WidGen::new_with_time_unit(4, 0, None, TimeUnit::Ms)
+            .unwrap()
+            .next_wid();
+        let fresh_w6 = WidGen::new(6, 0, None).unwrap().next_wid();
+        let verify = |wid: &str, extra: &[&str]| {
+            let code = compute_wotp("s3cret", wid, 6).unwrap();
+            let mut args: Vec<String> = ["A=w-otp", "MODE=verify", "KEY=s3cret"]
+                .iter()
+                .map(|a| a.to_string())
+                .chain([format!("WID={wid}"), format!("CODE={code}")])
+                .collect();
+            args.extend(extra.iter().map(|a| a.to_string()));
+            run_wotp(&parse_canonical(&args).unwrap())
+        };
+        for wid in [&fresh_ms, &fresh_w6] {
+            verify(wid, &["MAX_AGE_SEC=30"]).unwrap();
+        }
+        let err = verify("20200101T000000.0000Z", &["MAX_AGE_SEC=30"]).unwrap_err();
+        assert!(err.contains("too old"), "{err}");
+        let err = verify("29990101T000000123.0000Z", &[]).unwrap_err();
+        assert!(err.contains("future"), "{err}");
+        verify("20200101T000000.0000Z", &["MAX_AGE_SEC=0"]).unwrap();
+        verify(&fresh_ms, &["MAX_AGE_SEC=18446744073709551615"]).unwrap();
+    }
+
     #[test]
     fn test_parse_time_unit() {
         assert_eq!(parse_time_unit("sec").unwrap(), TimeUnit::Sec);
         assert_eq!(parse_time_unit("ms").unwrap(), TimeUnit::Ms);
+        assert_eq!(parse_time_unit("ns").unwrap(), TimeUnit::Ns);
         assert!(parse_time_unit("bad").is_err());
     }
 
@@ -1473,6 +2091,291 @@ mod tests {
         assert_eq!(opts.time_unit, TimeUnit::Ms);
     }
 
+    #[test]
+    fn test_parse_validate_window_flags() {
+        assert_eq!(parse_validate_flags(&[]).unwrap().max_age_sec, None);
+        let args: Vec<String> = ["--max-age-sec", "5m", "--max-future-sec", "2"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        let opts = parse_validate_flags(&args).unwrap();
+        assert_eq!(opts.max_age_sec, Some(300));
+        assert_eq!(opts.max_future_sec, Some(2));
+        assert!(parse_validate_flags(&["--max-age-sec".to_string()]).is_err());
+    }
+
+    #[test]
+    fn test_completions_offer_every_action() {
+        for shell in ["bash", "zsh", "fish"] {
+            let script = completion_script(shell).unwrap();
+            let line = script
+                .lines()
+                .find(|l| l.contains("A) vals") || l.ends_with("-d 'Action'"))
+                .unwrap();
+            let offered: Vec<&str> = line
+                .split(|c: char| c.is_whitespace() || "\"'()".contains(c))
+                .map(|word| word.strip_prefix("A=").unwrap_or(word))
+                .collect();
+            for action in CORE_ACTIONS.iter().chain(ORCHESTRATION_ACTIONS) {
+                assert!(offered.contains(action), "{shell} lacks {action}");
+            }
+        }
+        assert!(completion_script("tcsh").is_none());
+    }
+
+    #[test]
+    fn test_completions_offer_every_subcommand() {
+        for shell in ["bash", "zsh", "fish"] {
+            let script = completion_script(shell).unwrap();
+            for cmd in ["next", "validate", "convert-file", "sort", "diff"] {
+                let listed = script.lines().any(|l| {
+                    (l.contains("cmds") || l.contains("__fish_seen_subcommand_from"))
+                        && l.split(|c: char| c.is_whitespace() || "\"'()".contains(c))
+                            .any(|word| word == cmd)
+                });
+                assert!(listed, "{shell} lacks {cmd}");
+            }
+        }
+        let fish = completion_script("fish").unwrap();
+        assert!(fish.contains("-a sort -d"));
+        assert!(fish.contains("-a diff -d"));
+    }
+
+    #[test]
+    fn test_validate_max_future_alone_applies_window() {
+        let ahead = chrono::Utc::now().timestamp() + 60;
+        let wid = WidGen::mint_exact(ahead, 0, 4, 6, TimeUnit::Sec).unwrap();
+        let mut opts = ValidateOpts::default();
+        assert!(validate_with_opts(&wid, &opts));
+        opts.max_future_sec = Some(2);
+        assert!(!validate_with_opts(&wid, &opts));
+        opts.max_future_sec = Some(120);
+        assert!(validate_with_opts(&wid, &opts));
+        assert!(validate_with_opts("20200101T000000.0000Z-a3f91c", &opts));
+    }
+
+    #[test]
+    fn test_parse_duration_secs() {
+        assert_eq!(parse_duration_secs("90").unwrap(), 90);
+        assert_eq!(parse_duration_secs("30s").unwrap(), 30);
+        assert_eq!(parse_duration_secs("5m").unwrap(), 300);
+        assert_eq!(parse_duration_secs("2h").unwrap(), 7200);
+        assert!(parse_duration_secs("5d").unwrap_err().contains("suffix"));
+        assert!(parse_duration_secs("m").is_err());
+        assert!(parse_duration_secs("").is_err());
+
+        let c = parse_canonical(&["MAX_AGE_SEC=5m".to_string()]).unwrap();
+        assert_eq!(c.max_age_sec, 300);
+    }
+
+    #[test]
+    fn test_discover_payload_lists_features() {
+        let payload = discover_payload();
+        let features = payload["features"].as_object().unwrap();
+        assert_eq!(features.len(), 2);
+        assert_eq!(features["crossbeam"], cfg!(feature = "crossbeam"));
+        assert_eq!(features["serde"], cfg!(feature = "serde"));
+    }
+
+    #[test]
+    fn test_manifest_discover_aggregates_sections() {
+        let doc = manifest_discover_payload();
+        assert_eq!(doc["schema_version"], DISCOVERY_SCHEMA_VERSION);
+        let actions = doc["actions"].as_array().unwrap();
+        for action in ["next", "manifest-discover", "discover", "duplex"] {
+            assert!(actions.contains(&json!(action)), "missing action {action}");
+        }
+        assert_eq!(doc["transports"], discover_payload()["transports"]);
+        assert_eq!(doc["features"], discover_features());
+        assert_eq!(doc["version"], version_payload());
+    }
+
+    #[test]
+    fn test_version_payload_fields() {
+        let payload = version_payload();
+        assert_eq!(payload["crate_version"], env!("CARGO_PKG_VERSION"));
+        assert_eq!(payload["manifest_version"], MANIFEST_VERSION);
+        assert_eq!(payload["wid_format"], "WID/1");
+        assert_eq!(payload["time_units"], json!(["sec", "ms", "ns"]));
+    }
+
+    #[test]
+    fn test_ids_json_array() {
+        let generator = WidGen::new(4, 6, None).unwrap();
+        let out = ids_json_array(generator, 3).unwrap();
+        assert!(!out.contains('\n'));
+        let ids: Vec<String> = serde_json::from_str(&out).unwrap();
+        assert_eq!(ids.len(), 3);
+        assert!(
+            ids.iter()
+                .all(|id| validate_wid_with_unit(id, 4, 6, TimeUnit::Sec))
+        );
+        assert!(ids[0] < ids[1] && ids[1] < ids[2]);
+    }
+
+    #[test]
+    fn test_hlc_observe_loop_follows_remotes() {
+        // Remote physical times in the year 3000 stay ahead of the local clock.
+        let t = 32_503_680_000_i64;
+        let input = format!("{t},5\nnot-a-clock\n{t},2\n\n{},0\n-1,0\n", t + 1);
+        let mut generator = HLCWidGen::new("edge".to_string(), 4, 0).unwrap();
+        let (mut out, mut warn) = (Vec::new(), Vec::new());
+
+        let emitted =
+            hlc_observe_loop(&mut generator, input.as_bytes(), &mut out, &mut warn).unwrap();
+        assert_eq!(emitted, 3);
+
+        let ids: Vec<String> = String::from_utf8(out)
+            .unwrap()
+            .lines()
+            .map(String::from)
+            .collect();
+        assert!(ids.windows(2).all(|p| p[0] < p[1]));
+        let parsed: Vec<_> = ids
+            .iter()
+            .map(|id| parse_hlc_wid_with_unit(id, 4, 0, TimeUnit::Sec).unwrap())
+            .collect();
+        assert_eq!(parsed[0].timestamp.timestamp(), t);
+        assert!(parsed[0].logical_counter > 5);
+        assert!(parsed[1].logical_counter > parsed[0].logical_counter);
+        assert_eq!(parsed[2].timestamp.timestamp(), t + 1);
+        assert!(parsed.iter().all(|p| p.node == "edge"));
+
+        let warn = String::from_utf8(warn).unwrap();
+        assert_eq!(warn.lines().count(), 2);
+        assert!(warn.contains("line 2") && warn.contains("line 6"));
+    }
+
+    #[test]
+    fn test_convert_lines_counts_lossy_and_skipped() {
+        let input = "20260212T091530000.0001Z-a3f91c\n\
+                     garbage\n\
+                     20260212T091530250.0002Z-a3f91c\n";
+        let (mut out, mut warn) = (Vec::new(), Vec::new());
+        let stats = convert_lines(
+            input.as_bytes(),
+            &mut out,
+            &mut warn,
+            4,
+            6,
+            TimeUnit::Ms,
+            TimeUnit::Sec,
+        )
+        .unwrap();
+        assert_eq!(
+            stats,
+            ConvertStats {
+                converted: 2,
+                lossy: 1,
+                skipped: 1
+            }
+        );
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "20260212T091530.0001Z-a3f91c\n20260212T091530.0002Z-a3f91c\n"
+        );
+        assert!(String::from_utf8(warn).unwrap().contains("line 2"));
+    }
+
+    #[test]
+    fn test_sort_lines_orders_by_parsed_time() {
+        let opts = parse_validate_flags(&[
+            "--W".to_string(),
+            "4".to_string(),
+            "--Z".to_string(),
+            "0".to_string(),
+            "--time-unit".to_string(),
+            "ms".to_string(),
+        ])
+        .unwrap();
+        let input = "20260212T091530001.0000Z\n\
+                     garbage\n\
+                     20260212T091530000.0002Z\n\
+                     20260212T091530000.0001Z\n";
+        let mut out = Vec::new();
+        let mut warn = Vec::new();
+        let failed = sort_lines(input.as_bytes(), &mut out, &mut warn, &opts).unwrap();
+        assert_eq!(failed, 1);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "20260212T091530000.0001Z\n20260212T091530000.0002Z\n20260212T091530001.0000Z\n"
+        );
+        assert!(String::from_utf8(warn).unwrap().contains("line 2"));
+
+        let hlc_opts = parse_validate_flags(&["--kind".to_string(), "hlc".to_string()]).unwrap();
+        let input = "20260212T091530.0001Z-beta\n20260212T091530.0001Z-alpha\n";
+        let mut out = Vec::new();
+        let failed = sort_lines(input.as_bytes(), &mut out, &mut Vec::new(), &hlc_opts).unwrap();
+        assert_eq!(failed, 0);
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "20260212T091530.0001Z-alpha\n20260212T091530.0001Z-beta\n"
+        );
+    }
+
+    #[test]
+    fn test_diff_ids_text_and_json() {
+        let opts = ValidateOpts::default();
+        let diff = diff_ids(
+            "20260212T091530.0000Z-a3f91c",
+            "20260212T094200.0000Z-b1d23e",
+            &opts,
+        )
+        .unwrap();
+        assert_eq!(diff["delta"], 1590);
+        assert_eq!(
+            format_diff(&diff),
+            "delta=+1590s (26m 30s) sequence_delta=N/A"
+        );
+
+        let diff = diff_ids(
+            "20260212T091530.0003Z-a3f91c",
+            "20260212T091530.0001Z-b1d23e",
+            &opts,
+        )
+        .unwrap();
+        assert_eq!(format_diff(&diff), "delta=+0s sequence_delta=-2");
+
+        let hlc = parse_validate_flags(&[
+            "--kind".to_string(),
+            "hlc".to_string(),
+            "--time-unit".to_string(),
+            "ms".to_string(),
+        ])
+        .unwrap();
+        let diff = diff_ids(
+            "20260212T091530010.0001Z-node_a-a3f91c",
+            "20260212T091530005.0004Z-node_b-b1d23e",
+            &hlc,
+        )
+        .unwrap();
+        assert_eq!(diff["same_node"], false);
+        assert_eq!(
+            format_diff(&diff),
+            "delta=-5ms logical_counter_delta=+3 same_node=false"
+        );
+
+        assert!(diff_ids("bad", "20260212T091530.0000Z", &opts).is_err());
+        assert_eq!(format_span(90_061), "1d 1h 1m 1s");
+    }
+
+    #[test]
+    fn test_percentile_ns() {
+        let samples: Vec<u64> = (1..=100).collect();
+        assert_eq!(percentile_ns(&samples, 50.0), 50);
+        assert_eq!(percentile_ns(&samples, 99.0), 99);
+        assert_eq!(percentile_ns(&samples, 100.0), 100);
+        assert_eq!(percentile_ns(&[7], 50.0), 7);
+        assert_eq!(percentile_ns(&[], 99.0), 0);
+
+        let mut r = LatencyReservoir::new(10);
+        for ns in 1..=1000 {
+            r.record(ns);
+        }
+        assert_eq!(r.samples.len(), 10);
+        assert_eq!(r.max, 1000);
+    }
+
     #[test]
     fn test_parse_canonical_aliases() {
         let c =
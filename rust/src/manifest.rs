@@ -1,12 +1,18 @@
 //! SYNAPSE Manifest-Based Binary Files.
 
+use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs;
+use std::io::{BufReader, Read, Write};
 use std::path::Path;
+use std::sync::atomic::{AtomicU64, Ordering};
 use thiserror::Error;
 
+use crate::hlc::validate_hlc_wid_with_unit;
+use crate::wid::{TimeUnit, WidError, parse_wid_with_unit};
+
 /// Fixed magic bytes that prefix every SYNAPSE manifest file.
 pub const MANIFEST_MAGIC: &[u8; 4] = b"SYNM";
 /// Current manifest version baked into every file.
@@ -24,30 +30,162 @@ pub enum ManifestError {
     ManifestTooLarge(usize),
     #[error("Data too small for SYNAPSE file")]
     DataTooSmall,
+    #[error("Unsupported manifest version: {found} (max supported {MANIFEST_VERSION})")]
+    UnsupportedVersion { found: u16 },
+    #[error("Invalid WID: {0}")]
+    Wid(#[from] WidError),
+    #[error("Conflicting metadata key: {key}")]
+    MetadataConflict { key: String },
+    #[error("Invalid or missing flat-map field: {key}")]
+    InvalidField { key: String },
+    #[error("Invalid chunk set: {0}")]
+    InvalidChunks(String),
     #[error("IO error: {0}")]
     Io(#[from] std::io::Error),
     #[error("JSON error: {0}")]
     Json(#[from] serde_json::Error),
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
 /// Supported MIME-like types stored inside manifests.
+///
+/// Serialized as the bare MIME string; strings outside the fixed set
+/// deserialize to `Custom`.
 pub enum DataType {
     #[default]
-    #[serde(rename = "unknown")]
     Unknown,
-    #[serde(rename = "text/plain")]
     Text,
-    #[serde(rename = "application/json")]
     Json,
-    #[serde(rename = "application/octet-stream")]
     Binary,
+    /// Any other content type, stored verbatim.
+    Custom(String),
+}
+
+impl DataType {
+    /// Map one of the fixed MIME strings (or `unknown`) to its variant;
+    /// `None` for anything else.
+    pub fn from_mime_str(s: &str) -> Option<DataType> {
+        match s {
+            "unknown" => Some(DataType::Unknown),
+            "text/plain" => Some(DataType::Text),
+            "application/json" => Some(DataType::Json),
+            "application/octet-stream" => Some(DataType::Binary),
+            _ => None,
+        }
+    }
+
+    /// The MIME-like string stored in `Manifest::data_type`.
+    pub fn as_mime_str(&self) -> &str {
+        match self {
+            DataType::Unknown => "unknown",
+            DataType::Text => "text/plain",
+            DataType::Json => "application/json",
+            DataType::Binary => "application/octet-stream",
+            DataType::Custom(mime) => mime,
+        }
+    }
+
+    /// Guess the type of a payload: JSON objects/arrays, then UTF-8 text
+    /// without control characters (other than whitespace), else binary.
+    /// Empty payloads are `Unknown`.
+    pub fn sniff(payload: &[u8]) -> Self {
+        if payload.is_empty() {
+            return DataType::Unknown;
+        }
+        let starts_json = payload
+            .iter()
+            .find(|b| !b.is_ascii_whitespace())
+            .is_some_and(|b| matches!(b, b'{' | b'['));
+        if starts_json && serde_json::from_slice::<serde_json::Value>(payload).is_ok() {
+            return DataType::Json;
+        }
+        match std::str::from_utf8(payload) {
+            Ok(text) if !text.chars().any(|c| c.is_control() && !c.is_whitespace()) => {
+                DataType::Text
+            }
+            _ => DataType::Binary,
+        }
+    }
+}
+
+impl From<&str> for DataType {
+    fn from(s: &str) -> Self {
+        DataType::from_mime_str(s).unwrap_or_else(|| DataType::Custom(s.to_string()))
+    }
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
+impl Serialize for DataType {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_mime_str())
+    }
+}
+
+impl<'de> Deserialize<'de> for DataType {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        Ok(DataType::from_mime_str(&s).unwrap_or(DataType::Custom(s)))
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+/// Digest used for `Manifest::data_hash`.
+pub enum HashAlgorithm {
+    #[default]
+    #[serde(rename = "sha256")]
+    Sha256,
+    #[serde(rename = "blake3")]
+    Blake3,
+}
+
+impl HashAlgorithm {
+    /// The name stored in `Manifest::hash_algorithm`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            HashAlgorithm::Sha256 => "sha256",
+            HashAlgorithm::Blake3 => "blake3",
+        }
+    }
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "sha256" => Some(HashAlgorithm::Sha256),
+            "blake3" => Some(HashAlgorithm::Blake3),
+            _ => None,
+        }
+    }
+
+    /// Hex digest of `data`.
+    pub fn hex_digest(self, data: &[u8]) -> String {
+        match self {
+            HashAlgorithm::Sha256 => hex::encode(Sha256::digest(data)),
+            HashAlgorithm::Blake3 => blake3::hash(data).to_hex().to_string(),
+        }
+    }
+
+    fn is_default(&self) -> bool {
+        *self == HashAlgorithm::Sha256
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+/// How `Manifest::merge_from` resolves a metadata key set to different values
+/// on both sides.
+pub enum MergePolicy {
+    /// Keep this manifest's value.
+    PreferSelf,
+    /// Take the other manifest's value.
+    PreferOther,
+    /// Fail with `ManifestError::MetadataConflict`, leaving `self` untouched.
+    Error,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 /// Manifest metadata container with serialization helpers.
 pub struct Manifest {
     pub id: String,
+    /// WID of the event this file belongs to, if any.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wid: Option<String>,
     #[serde(default = "default_version")]
     pub version: u16,
     #[serde(default)]
@@ -58,6 +196,16 @@ pub struct Manifest {
     pub data_size: usize,
     #[serde(default)]
     pub data_hash: String,
+    /// Digest behind `data_hash`. Omitted from JSON when `Sha256`, so
+    /// manifests written before this field existed read back unchanged.
+    #[serde(default, skip_serializing_if = "HashAlgorithm::is_default")]
+    pub hash_algorithm: HashAlgorithm,
+    /// When the file was created, as RFC 3339.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub created_at: Option<DateTime<Utc>>,
+    /// When the file may be discarded, as RFC 3339; see `is_expired`.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub expires_at: Option<DateTime<Utc>>,
     #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub metadata: HashMap<String, serde_json::Value>,
 }
@@ -66,19 +214,93 @@ fn default_version() -> u16 {
     MANIFEST_VERSION
 }
 
+/// Metadata key holding the array built by `Manifest::with_tag`.
+const TAGS_KEY: &str = "tags";
+
+/// Prefix of metadata entries in `Manifest::to_flat_map`.
+const FLAT_METADATA_PREFIX: &str = "metadata.";
+
+/// Metadata key holding a chunk's 0-based position; see
+/// `SynapseFile::split_into_chunks`.
+pub const CHUNK_INDEX_KEY: &str = "chunk_index";
+/// Metadata key holding how many chunks the parent was split into.
+pub const CHUNK_TOTAL_KEY: &str = "chunk_total";
+/// Metadata key holding the `id` of the file a chunk was split from.
+pub const PARENT_ID_KEY: &str = "parent_id";
+
+/// Reserved metadata key holding the generator's `W`.
+pub const GENERATOR_W_KEY: &str = "wid.w";
+/// Reserved metadata key holding the generator's `Z`.
+pub const GENERATOR_Z_KEY: &str = "wid.z";
+/// Reserved metadata key holding the generator's time unit (`sec`/`ms`/`ns`).
+pub const GENERATOR_TIME_UNIT_KEY: &str = "wid.time_unit";
+
 impl Manifest {
     pub fn new(id: impl Into<String>) -> Self {
         Self {
             id: id.into(),
+            wid: None,
             version: MANIFEST_VERSION,
             node: String::new(),
             data_type: "unknown".to_string(),
             data_size: 0,
             data_hash: String::new(),
+            hash_algorithm: HashAlgorithm::Sha256,
+            created_at: None,
+            expires_at: None,
             metadata: HashMap::new(),
         }
     }
 
+    /// Whether `expires_at` has passed. Manifests without one never expire.
+    pub fn is_expired(&self) -> bool {
+        self.expires_at.is_some_and(|t| Utc::now() > t)
+    }
+
+    /// Set the WID of the event this file belongs to.
+    pub fn with_wid(mut self, wid: impl Into<String>) -> Self {
+        self.wid = Some(wid.into());
+        self
+    }
+
+    pub fn with_node(mut self, node: impl Into<String>) -> Self {
+        self.node = node.into();
+        self
+    }
+
+    pub fn with_data_type(mut self, data_type: DataType) -> Self {
+        self.data_type = data_type.as_mime_str().to_string();
+        self
+    }
+
+    /// Insert `key` into `metadata`, replacing any previous value.
+    pub fn with_metadata_entry(
+        mut self,
+        key: impl Into<String>,
+        value: impl Into<serde_json::Value>,
+    ) -> Self {
+        self.metadata.insert(key.into(), value.into());
+        self
+    }
+
+    /// Append `tag` to the `tags` metadata array, creating it if needed.
+    /// Duplicate tags are kept once; a non-array `tags` entry is replaced.
+    pub fn with_tag(mut self, tag: &str) -> Self {
+        let tags = self
+            .metadata
+            .entry(TAGS_KEY.to_string())
+            .or_insert_with(|| serde_json::Value::Array(Vec::new()));
+        if !tags.is_array() {
+            *tags = serde_json::Value::Array(Vec::new());
+        }
+        if let serde_json::Value::Array(tags) = tags
+            && !tags.iter().any(|existing| existing == tag)
+        {
+            tags.push(tag.into());
+        }
+        self
+    }
+
     pub fn to_json(&self) -> Result<String, ManifestError> {
         Ok(serde_json::to_string_pretty(self)?)
     }
@@ -86,9 +308,131 @@ impl Manifest {
     pub fn from_json(data: &str) -> Result<Self, ManifestError> {
         Ok(serde_json::from_str(data)?)
     }
+
+    /// Flatten into a string map, e.g. for MQTT user properties.
+    ///
+    /// Top-level fields keep their names (`wid` only when set); each metadata
+    /// entry becomes `metadata.<key>` with its value encoded as JSON, so nested
+    /// values survive `from_flat_map` unchanged.
+    pub fn to_flat_map(&self) -> BTreeMap<String, String> {
+        let mut map = BTreeMap::from([
+            ("id".to_string(), self.id.clone()),
+            ("version".to_string(), self.version.to_string()),
+            ("node".to_string(), self.node.clone()),
+            ("data_type".to_string(), self.data_type.clone()),
+            ("data_size".to_string(), self.data_size.to_string()),
+            ("data_hash".to_string(), self.data_hash.clone()),
+        ]);
+        if let Some(wid) = &self.wid {
+            map.insert("wid".to_string(), wid.clone());
+        }
+        for (key, ts) in [
+            ("created_at", self.created_at),
+            ("expires_at", self.expires_at),
+        ] {
+            if let Some(ts) = ts {
+                map.insert(key.to_string(), ts.to_rfc3339());
+            }
+        }
+        if !self.hash_algorithm.is_default() {
+            map.insert(
+                "hash_algorithm".to_string(),
+                self.hash_algorithm.as_str().to_string(),
+            );
+        }
+        for (key, value) in &self.metadata {
+            map.insert(format!("{FLAT_METADATA_PREFIX}{key}"), value.to_string());
+        }
+        map
+    }
+
+    /// Rebuild a manifest from `to_flat_map` output.
+    ///
+    /// `id` is required; other top-level fields fall back to the same defaults
+    /// as JSON deserialization. Unknown keys and unparseable numbers or
+    /// metadata JSON fail with `ManifestError::InvalidField`.
+    pub fn from_flat_map(map: &BTreeMap<String, String>) -> Result<Self, ManifestError> {
+        let invalid = |key: &str| ManifestError::InvalidField {
+            key: key.to_string(),
+        };
+        let id = map.get("id").ok_or_else(|| invalid("id"))?;
+        let mut manifest = Manifest::new(id.clone());
+        manifest.data_type = String::new();
+        for (key, value) in map {
+            match key.as_str() {
+                "id" => {}
+                "wid" => manifest.wid = Some(value.clone()),
+                "version" => manifest.version = value.parse().map_err(|_| invalid(key))?,
+                "node" => manifest.node = value.clone(),
+                "data_type" => manifest.data_type = value.clone(),
+                "data_size" => manifest.data_size = value.parse().map_err(|_| invalid(key))?,
+                "data_hash" => manifest.data_hash = value.clone(),
+                "hash_algorithm" => {
+                    manifest.hash_algorithm =
+                        HashAlgorithm::parse(value).ok_or_else(|| invalid(key))?
+                }
+                "created_at" | "expires_at" => {
+                    let ts = DateTime::parse_from_rfc3339(value)
+                        .map_err(|_| invalid(key))?
+                        .with_timezone(&Utc);
+                    if key == "created_at" {
+                        manifest.created_at = Some(ts);
+                    } else {
+                        manifest.expires_at = Some(ts);
+                    }
+                }
+                _ => {
+                    let meta_key = key
+                        .strip_prefix(FLAT_METADATA_PREFIX)
+                        .ok_or_else(|| invalid(key))?;
+                    let meta_value = serde_json::from_str(value).map_err(|_| invalid(key))?;
+                    manifest.metadata.insert(meta_key.to_string(), meta_value);
+                }
+            }
+        }
+        Ok(manifest)
+    }
+
+    /// Record the configuration of the generator that minted `wid` under the
+    /// reserved `wid.*` metadata keys, for `WidGen::from_manifest`.
+    pub fn set_generator_config(&mut self, w: usize, z: usize, time_unit: TimeUnit) {
+        self.metadata.insert(GENERATOR_W_KEY.to_string(), w.into());
+        self.metadata.insert(GENERATOR_Z_KEY.to_string(), z.into());
+        self.metadata.insert(
+            GENERATOR_TIME_UNIT_KEY.to_string(),
+            time_unit.as_str().into(),
+        );
+    }
+
+    /// Copy `other`'s metadata into this manifest. Keys with equal values on
+    /// both sides are not conflicts; other collisions follow `policy`.
+    pub fn merge_from(
+        &mut self,
+        other: &Manifest,
+        policy: MergePolicy,
+    ) -> Result<(), ManifestError> {
+        if policy == MergePolicy::Error
+            && let Some(key) = other
+                .metadata
+                .iter()
+                .find(|(k, v)| self.metadata.get(*k).is_some_and(|mine| mine != *v))
+                .map(|(k, _)| k)
+        {
+            return Err(ManifestError::MetadataConflict { key: key.clone() });
+        }
+
+        for (key, value) in &other.metadata {
+            if policy == MergePolicy::PreferSelf && self.metadata.contains_key(key) {
+                continue;
+            }
+            self.metadata.insert(key.clone(), value.clone());
+        }
+        Ok(())
+    }
 }
 
 /// Combined manifest payload used for SYNAPSE file blobs.
+#[derive(Clone)]
 pub struct SynapseFile {
     pub manifest: Manifest,
     pub payload: Vec<u8>,
@@ -99,10 +443,79 @@ impl SynapseFile {
         Self { manifest, payload }
     }
 
-    pub fn to_bytes(&mut self) -> Result<Vec<u8>, ManifestError> {
+    /// Like `new`, stamping `created_at` with the current time and
+    /// `expires_at` with `ttl` later. A `ttl` past chrono's range leaves
+    /// `expires_at` unset, so the file never expires.
+    pub fn new_with_ttl(
+        mut manifest: Manifest,
+        payload: Vec<u8>,
+        ttl: std::time::Duration,
+    ) -> Self {
+        let now = Utc::now();
+        manifest.created_at = Some(now);
+        manifest.expires_at = chrono::Duration::from_std(ttl)
+            .ok()
+            .and_then(|ttl| now.checked_add_signed(ttl));
+        Self::new(manifest, payload)
+    }
+
+    /// Wrap `payload` for the event `wid`, which becomes both the manifest
+    /// `id` and `wid`. The WID or HLC-WID must match `w`, `z` and
+    /// `time_unit`; the data type is sniffed from the payload.
+    pub fn for_wid(
+        wid: &str,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        payload: Vec<u8>,
+    ) -> Result<Self, ManifestError> {
+        if !validate_hlc_wid_with_unit(wid, w, z, time_unit) {
+            parse_wid_with_unit(wid, w, z, time_unit)?;
+        }
+        let mut manifest = Manifest::new(wid);
+        manifest.wid = Some(wid.to_string());
+        manifest.data_type = DataType::sniff(&payload).as_mime_str().to_string();
+        let mut file = Self::new(manifest, payload);
+        file.refresh_hash();
+        Ok(file)
+    }
+
+    /// Read the payload in place, e.g. to hash it or copy it to a socket
+    /// without cloning.
+    pub fn payload_reader(&self) -> impl std::io::Read + '_ {
+        std::io::Cursor::new(self.payload.as_slice())
+    }
+
+    /// Append to the payload, e.g. from a streaming encoder. `data_size` and
+    /// `data_hash` go stale until `refresh_hash` or `to_bytes`.
+    pub fn payload_writer(&mut self) -> impl std::io::Write + '_ {
+        &mut self.payload
+    }
+
+    /// Content address of the file: SHA-256 hex over the manifest serialized
+    /// with sorted keys, with `data_size` and `data_hash` recomputed from the
+    /// payload. Files with equal payloads and logically equal manifests share
+    /// an id regardless of metadata insertion order or a stale hash.
+    pub fn content_id(&self) -> String {
+        let mut manifest = self.manifest.clone();
+        manifest.data_size = self.payload.len();
+        manifest.data_hash = manifest.hash_algorithm.hex_digest(&self.payload);
+        let canonical = serde_json::to_value(&manifest)
+            .map(|value| canonical_json(&value))
+            .unwrap_or_default();
+        hex::encode(Sha256::digest(canonical.as_bytes()))
+    }
+
+    /// Recompute `data_size` and `data_hash` from the current payload in place
+    /// and return the new hash, without serializing the file.
+    pub fn refresh_hash(&mut self) -> &str {
         self.manifest.data_size = self.payload.len();
-        let hash = Sha256::digest(&self.payload);
-        self.manifest.data_hash = hex::encode(hash);
+        self.manifest.data_hash = self.manifest.hash_algorithm.hex_digest(&self.payload);
+        &self.manifest.data_hash
+    }
+
+    pub fn to_bytes(&mut self) -> Result<Vec<u8>, ManifestError> {
+        self.refresh_hash();
 
         let manifest_bytes = self.manifest.to_json()?.into_bytes();
         if manifest_bytes.len() > MAX_MANIFEST_SIZE {
@@ -119,25 +532,77 @@ impl SynapseFile {
         Ok(result)
     }
 
+    /// Decode a SYNAPSE blob, rejecting header versions newer than
+    /// `MANIFEST_VERSION` with `ManifestError::UnsupportedVersion`.
     pub fn from_bytes(data: &[u8]) -> Result<Self, ManifestError> {
+        Self::decode(data, false)
+    }
+
+    /// Like `from_bytes`, but accept newer header versions for forward-compatible
+    /// reads. The manifest body must still parse.
+    pub fn from_bytes_allow_newer(data: &[u8]) -> Result<Self, ManifestError> {
+        Self::decode(data, true)
+    }
+
+    /// Decode only the manifest of a SYNAPSE blob, ignoring the payload bytes.
+    /// Header checks match `from_bytes`.
+    pub fn from_bytes_header_only(data: &[u8]) -> Result<Manifest, ManifestError> {
+        let manifest_end = Self::manifest_end(data, false)?;
+        decode_manifest(&data[HEADER_SIZE..manifest_end])
+    }
+
+    /// Read only the header and manifest of an embedded SYNAPSE file, leaving
+    /// the payload on disk. Sidecar and raw files are rejected with
+    /// `InvalidMagic`; use `load` for those.
+    pub fn load_header_only(path: &Path) -> Result<Manifest, ManifestError> {
+        let eof_is_short = |e: std::io::Error| match e.kind() {
+            std::io::ErrorKind::UnexpectedEof => ManifestError::DataTooSmall,
+            _ => ManifestError::Io(e),
+        };
+        let mut reader = BufReader::new(fs::File::open(path)?);
+        let mut header = [0u8; HEADER_SIZE];
+        reader.read_exact(&mut header).map_err(eof_is_short)?;
+        let manifest_size = Self::parse_header(&header, false)?;
+        let mut manifest_bytes = vec![0u8; manifest_size];
+        reader
+            .read_exact(&mut manifest_bytes)
+            .map_err(eof_is_short)?;
+        decode_manifest(&manifest_bytes)
+    }
+
+    /// Check the fixed header at the start of `data` and return its declared
+    /// manifest size.
+    fn parse_header(data: &[u8], allow_newer: bool) -> Result<usize, ManifestError> {
         if data.len() < HEADER_SIZE {
             return Err(ManifestError::DataTooSmall);
         }
         if &data[0..4] != MANIFEST_MAGIC {
             return Err(ManifestError::InvalidMagic);
         }
+        let version = u16::from_be_bytes([data[4], data[5]]);
+        if version > MANIFEST_VERSION && !allow_newer {
+            return Err(ManifestError::UnsupportedVersion { found: version });
+        }
         let manifest_size = u32::from_be_bytes([data[6], data[7], data[8], data[9]]) as usize;
         if manifest_size > MAX_MANIFEST_SIZE {
             return Err(ManifestError::ManifestTooLarge(manifest_size));
         }
-        let manifest_end = HEADER_SIZE + manifest_size;
+        Ok(manifest_size)
+    }
+
+    /// `parse_header`, then check `data` holds the whole manifest and return
+    /// the offset where it ends.
+    fn manifest_end(data: &[u8], allow_newer: bool) -> Result<usize, ManifestError> {
+        let manifest_end = HEADER_SIZE + Self::parse_header(data, allow_newer)?;
         if manifest_end > data.len() {
             return Err(ManifestError::DataTooSmall);
         }
-        let manifest_str = std::str::from_utf8(&data[HEADER_SIZE..manifest_end]).map_err(|e| {
-            ManifestError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e))
-        })?;
-        let manifest = Manifest::from_json(manifest_str)?;
+        Ok(manifest_end)
+    }
+
+    fn decode(data: &[u8], allow_newer: bool) -> Result<Self, ManifestError> {
+        let manifest_end = Self::manifest_end(data, allow_newer)?;
+        let manifest = decode_manifest(&data[HEADER_SIZE..manifest_end])?;
         let payload = data[manifest_end..].to_vec();
         Ok(Self { manifest, payload })
     }
@@ -154,6 +619,21 @@ impl SynapseFile {
         Ok(())
     }
 
+    /// Like `save`, but each file is replaced atomically and durably: written
+    /// to a temp file in the same directory, fsynced, renamed over the target,
+    /// and the directory fsynced so the rename survives power loss.
+    pub fn save_durable(&mut self, path: &Path, embed: bool) -> Result<(), ManifestError> {
+        if embed {
+            write_durable(path, &self.to_bytes()?)?;
+        } else {
+            write_durable(path, &self.payload)?;
+            let ext = path.extension().unwrap_or_default().to_string_lossy();
+            let manifest_path = path.with_extension(format!("{ext}.manifest.json"));
+            write_durable(&manifest_path, self.manifest.to_json()?.as_bytes())?;
+        }
+        Ok(())
+    }
+
     pub fn load(path: &Path) -> Result<Self, ManifestError> {
         let data = fs::read(path)?;
         if data.len() >= 4 && &data[0..4] == MANIFEST_MAGIC {
@@ -168,7 +648,7 @@ impl SynapseFile {
                 payload: data,
             });
         }
-        let hash = hex::encode(Sha256::digest(&data));
+        let hash = HashAlgorithm::default().hex_digest(&data);
         Ok(Self {
             manifest: Manifest {
                 id: path
@@ -184,10 +664,198 @@ impl SynapseFile {
         })
     }
 
+    /// Whether the payload matches `data_hash` under the manifest's
+    /// `hash_algorithm`.
     pub fn verify(&self) -> bool {
-        let hash = hex::encode(Sha256::digest(&self.payload));
+        let hash = self.manifest.hash_algorithm.hex_digest(&self.payload);
         hash == self.manifest.data_hash
     }
+
+    /// Split the payload into `ceil(len / chunk_size)` files for backends
+    /// with per-object size limits; an empty payload still yields one chunk.
+    ///
+    /// Each chunk carries a copy of this manifest with its own `data_size`
+    /// and `data_hash`, `id` set to `<id>#<index>`, and the
+    /// `chunk_index`/`chunk_total`/`parent_id` metadata that
+    /// `merge_chunks` needs. This file's hash is refreshed first.
+    pub fn split_into_chunks(
+        &mut self,
+        chunk_size: usize,
+    ) -> Result<Vec<SynapseFile>, ManifestError> {
+        if chunk_size == 0 {
+            return Err(ManifestError::InvalidChunks(
+                "chunk size must be positive".to_string(),
+            ));
+        }
+        self.refresh_hash();
+        let total = self.payload.len().div_ceil(chunk_size).max(1);
+        let chunks = (0..total).map(|index| {
+            let start = (index * chunk_size).min(self.payload.len());
+            let end = (start + chunk_size).min(self.payload.len());
+            let mut manifest = self.manifest.clone();
+            manifest.id = format!("{}#{index}", self.manifest.id);
+            manifest
+                .metadata
+                .insert(CHUNK_INDEX_KEY.to_string(), index.into());
+            manifest
+                .metadata
+                .insert(CHUNK_TOTAL_KEY.to_string(), total.into());
+            manifest
+                .metadata
+                .insert(PARENT_ID_KEY.to_string(), self.manifest.id.clone().into());
+            let mut chunk = SynapseFile::new(manifest, self.payload[start..end].to_vec());
+            chunk.refresh_hash();
+            chunk
+        });
+        Ok(chunks.collect())
+    }
+
+    /// Reassemble the output of `split_into_chunks`, in any order.
+    ///
+    /// Every chunk must verify against its own hash and agree on `parent_id`
+    /// and `chunk_total`, and the indices must be exactly `0..chunk_total`.
+    /// The parent manifest is rebuilt from the first chunk's, with the chunk
+    /// metadata removed, `id` restored and the hash recomputed over the whole
+    /// payload.
+    pub fn merge_chunks(mut chunks: Vec<SynapseFile>) -> Result<SynapseFile, ManifestError> {
+        let invalid = ManifestError::InvalidChunks;
+        let first = chunks
+            .first()
+            .ok_or_else(|| invalid("no chunks".to_string()))?;
+        let (parent_id, total) = chunk_header(first)?;
+        if total != chunks.len() {
+            return Err(invalid(format!(
+                "expected {total} chunks, got {}",
+                chunks.len()
+            )));
+        }
+        for chunk in &chunks {
+            let (id, chunk_total) = chunk_header(chunk)?;
+            if id != parent_id || chunk_total != total {
+                return Err(invalid(format!(
+                    "{} belongs to another file",
+                    chunk.manifest.id
+                )));
+            }
+            if !chunk.verify() {
+                return Err(invalid(format!("{} fails its hash", chunk.manifest.id)));
+            }
+            chunk_index(chunk)?;
+        }
+        chunks.sort_by_key(|chunk| chunk_index(chunk).unwrap_or(usize::MAX));
+        if chunks
+            .iter()
+            .enumerate()
+            .any(|(i, chunk)| chunk_index(chunk).ok() != Some(i))
+        {
+            return Err(invalid("chunk indices are not 0..chunk_total".to_string()));
+        }
+
+        let mut manifest = chunks[0].manifest.clone();
+        for key in [CHUNK_INDEX_KEY, CHUNK_TOTAL_KEY, PARENT_ID_KEY] {
+            manifest.metadata.remove(key);
+        }
+        manifest.id = parent_id;
+        let payload = chunks.into_iter().flat_map(|chunk| chunk.payload).collect();
+        let mut file = SynapseFile::new(manifest, payload);
+        file.refresh_hash();
+        Ok(file)
+    }
+}
+
+/// `(parent_id, chunk_total)` of a chunk from `split_into_chunks`.
+fn chunk_header(chunk: &SynapseFile) -> Result<(String, usize), ManifestError> {
+    let metadata = &chunk.manifest.metadata;
+    let parent_id = metadata.get(PARENT_ID_KEY).and_then(|v| v.as_str());
+    let total = metadata.get(CHUNK_TOTAL_KEY).and_then(|v| v.as_u64());
+    match (parent_id, total) {
+        (Some(parent_id), Some(total)) => Ok((parent_id.to_string(), total as usize)),
+        _ => Err(ManifestError::InvalidChunks(format!(
+            "{} is missing {PARENT_ID_KEY} or {CHUNK_TOTAL_KEY}",
+            chunk.manifest.id
+        ))),
+    }
+}
+
+fn chunk_index(chunk: &SynapseFile) -> Result<usize, ManifestError> {
+    chunk
+        .manifest
+        .metadata
+        .get(CHUNK_INDEX_KEY)
+        .and_then(|v| v.as_u64())
+        .map(|index| index as usize)
+        .ok_or_else(|| {
+            ManifestError::InvalidChunks(format!(
+                "{} is missing {CHUNK_INDEX_KEY}",
+                chunk.manifest.id
+            ))
+        })
+}
+
+/// Compact JSON with object keys sorted at every level.
+fn canonical_json(value: &serde_json::Value) -> String {
+    match value {
+        serde_json::Value::Object(map) => {
+            let mut entries: Vec<_> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let fields: Vec<String> = entries
+                .into_iter()
+                .map(|(k, v)| {
+                    format!(
+                        "{}:{}",
+                        serde_json::Value::from(k.as_str()),
+                        canonical_json(v)
+                    )
+                })
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        serde_json::Value::Array(items) => {
+            let items: Vec<String> = items.iter().map(canonical_json).collect();
+            format!("[{}]", items.join(","))
+        }
+        other => other.to_string(),
+    }
+}
+
+fn decode_manifest(bytes: &[u8]) -> Result<Manifest, ManifestError> {
+    let manifest_str = std::str::from_utf8(bytes)
+        .map_err(|e| ManifestError::Io(std::io::Error::new(std::io::ErrorKind::InvalidData, e)))?;
+    Manifest::from_json(manifest_str)
+}
+
+/// Distinguishes temp files of concurrent `write_durable` calls within one
+/// process; the PID alone only separates processes.
+static DURABLE_TMP_SEQ: AtomicU64 = AtomicU64::new(0);
+
+fn write_durable(path: &Path, bytes: &[u8]) -> std::io::Result<()> {
+    let name = path.file_name().unwrap_or_default().to_string_lossy();
+    let seq = DURABLE_TMP_SEQ.fetch_add(1, Ordering::Relaxed);
+    let tmp = path.with_file_name(format!(".{name}.{}.{seq}.tmp", std::process::id()));
+    let result = (|| {
+        let mut file = fs::OpenOptions::new()
+            .write(true)
+            .create_new(true)
+            .open(&tmp)?;
+        file.write_all(bytes)?;
+        file.sync_all()?;
+        fs::rename(&tmp, path)
+    })();
+    if result.is_err() {
+        let _ = fs::remove_file(&tmp);
+    }
+    result?;
+
+    // Directories can only be opened for syncing on Unix.
+    #[cfg(unix)]
+    {
+        let dir = match path.parent() {
+            Some(parent) if !parent.as_os_str().is_empty() => parent,
+            _ => Path::new("."),
+        };
+        fs::File::open(dir)?.sync_all()?;
+    }
+    Ok(())
 }
 
 #[cfg(test)]
@@ -218,6 +886,226 @@ mod tests {
         assert!(loaded.verify());
     }
 
+    #[test]
+    fn test_builder_methods() {
+        let manifest = Manifest::new("m1")
+            .with_wid("20260212T091530.0000Z-a3f91c")
+            .with_node("edge_1")
+            .with_data_type(DataType::Json)
+            .with_metadata_entry("unit", "celsius")
+            .with_tag("sensor")
+            .with_tag("raw")
+            .with_tag("sensor");
+        assert_eq!(
+            manifest.wid.as_deref(),
+            Some("20260212T091530.0000Z-a3f91c")
+        );
+        assert_eq!(manifest.node, "edge_1");
+        assert_eq!(manifest.data_type, "application/json");
+        assert_eq!(manifest.metadata["unit"], "celsius");
+        assert_eq!(
+            manifest.metadata["tags"],
+            serde_json::json!(["sensor", "raw"])
+        );
+    }
+
+    #[test]
+    fn test_merge_from_policies() {
+        let mut ours = Manifest::new("a");
+        ours.metadata
+            .insert("owner".into(), serde_json::json!("alice"));
+        ours.metadata.insert("shared".into(), serde_json::json!(1));
+        let mut theirs = Manifest::new("b");
+        theirs
+            .metadata
+            .insert("owner".into(), serde_json::json!("bob"));
+        theirs
+            .metadata
+            .insert("shared".into(), serde_json::json!(1));
+        theirs
+            .metadata
+            .insert("extra".into(), serde_json::json!(true));
+
+        let mut m = ours.clone();
+        m.merge_from(&theirs, MergePolicy::PreferSelf).unwrap();
+        assert_eq!(m.metadata["owner"], "alice");
+        assert_eq!(m.metadata["extra"], true);
+
+        let mut m = ours.clone();
+        m.merge_from(&theirs, MergePolicy::PreferOther).unwrap();
+        assert_eq!(m.metadata["owner"], "bob");
+        assert_eq!(m.metadata.len(), 3);
+
+        let mut m = ours.clone();
+        let err = m.merge_from(&theirs, MergePolicy::Error).unwrap_err();
+        assert!(matches!(err, ManifestError::MetadataConflict { key } if key == "owner"));
+        assert_eq!(m.metadata, ours.metadata);
+    }
+
+    #[test]
+    fn test_ttl_and_expiry() {
+        let sf = SynapseFile::new_with_ttl(
+            Manifest::new("x"),
+            b"data".to_vec(),
+            std::time::Duration::from_secs(3600),
+        );
+        let created = sf.manifest.created_at.unwrap();
+        assert_eq!(
+            sf.manifest.expires_at.unwrap() - created,
+            chrono::Duration::hours(1)
+        );
+        assert!(!sf.manifest.is_expired());
+
+        let json = sf.manifest.to_json().unwrap();
+        assert!(json.contains("\"expires_at\""));
+        assert_eq!(Manifest::from_json(&json).unwrap(), sf.manifest);
+        let flat = sf.manifest.to_flat_map();
+        assert_eq!(Manifest::from_flat_map(&flat).unwrap(), sf.manifest);
+
+        let mut expired = Manifest::new("old");
+        expired.expires_at = Some(Utc::now() - chrono::Duration::seconds(1));
+        assert!(expired.is_expired());
+
+        let forever =
+            SynapseFile::new_with_ttl(Manifest::new("y"), vec![], std::time::Duration::MAX);
+        assert_eq!(forever.manifest.expires_at, None);
+
+        let legacy = Manifest::from_json(r#"{"id":"old"}"#).unwrap();
+        assert_eq!((legacy.created_at, legacy.expires_at), (None, None));
+        assert!(!legacy.is_expired());
+    }
+
+    #[test]
+    fn test_flat_map_round_trip() {
+        let mut manifest = Manifest::new("20260212T091530.0000Z-a3f91c");
+        manifest.wid = Some(manifest.id.clone());
+        manifest.node = "edge01".to_string();
+        manifest.data_size = 42;
+        manifest.metadata.insert(
+            "sensor".to_string(),
+            serde_json::json!({"kind": "temp", "range": [-40, 85], "cal": {"a": 1.5}}),
+        );
+        manifest
+            .metadata
+            .insert("site.name".to_string(), serde_json::json!("lab"));
+
+        let flat = manifest.to_flat_map();
+        assert_eq!(flat["data_size"], "42");
+        assert_eq!(flat["metadata.site.name"], "\"lab\"");
+        assert!(flat["metadata.sensor"].contains("\"range\":[-40,85]"));
+        assert_eq!(Manifest::from_flat_map(&flat).unwrap(), manifest);
+
+        let mut bad = flat.clone();
+        bad.insert("extra".to_string(), "x".to_string());
+        assert!(matches!(
+            Manifest::from_flat_map(&bad),
+            Err(ManifestError::InvalidField { key }) if key == "extra"
+        ));
+        bad.remove("extra");
+        bad.remove("id");
+        assert!(matches!(
+            Manifest::from_flat_map(&bad),
+            Err(ManifestError::InvalidField { key }) if key == "id"
+        ));
+    }
+
+    #[test]
+    fn test_data_type_mime_strings() {
+        for data_type in [
+            DataType::Unknown,
+            DataType::Text,
+            DataType::Json,
+            DataType::Binary,
+        ] {
+            assert_eq!(
+                DataType::from_mime_str(data_type.as_mime_str()),
+                Some(data_type)
+            );
+        }
+        assert_eq!(DataType::from_mime_str("image/png"), None);
+
+        let custom = DataType::from("image/png");
+        assert_eq!(custom, DataType::Custom("image/png".to_string()));
+        assert_eq!(serde_json::to_string(&custom).unwrap(), r#""image/png""#);
+        assert_eq!(
+            serde_json::from_str::<DataType>(r#""image/png""#).unwrap(),
+            custom
+        );
+        assert_eq!(
+            serde_json::from_str::<DataType>(r#""text/plain""#).unwrap(),
+            DataType::Text
+        );
+
+        let manifest = Manifest::new("x").with_data_type(custom);
+        assert_eq!(manifest.data_type, "image/png");
+    }
+
+    #[test]
+    fn test_for_wid_tags_manifest() {
+        let wid = "20260212T091530.0000Z-a3f91c";
+        let sf =
+            SynapseFile::for_wid(wid, 4, 6, TimeUnit::Sec, br#"{"temp": 21.5}"#.to_vec()).unwrap();
+        assert_eq!(sf.manifest.id, wid);
+        assert_eq!(sf.manifest.wid.as_deref(), Some(wid));
+        assert_eq!(sf.manifest.data_type, "application/json");
+        assert!(sf.verify());
+
+        let hlc = "20260212T091530.0000Z-node01-a3f91c";
+        let sf = SynapseFile::for_wid(hlc, 4, 6, TimeUnit::Sec, b"hello".to_vec()).unwrap();
+        assert_eq!(sf.manifest.data_type, "text/plain");
+
+        let ms = "20260212T091530123.00Z";
+        assert!(SynapseFile::for_wid(ms, 2, 0, TimeUnit::Ms, vec![]).is_ok());
+        assert!(SynapseFile::for_wid(ms, 4, 6, TimeUnit::Sec, vec![]).is_err());
+
+        assert!(matches!(
+            SynapseFile::for_wid("not-a-wid", 4, 6, TimeUnit::Sec, vec![]),
+            Err(ManifestError::Wid(_))
+        ));
+        assert_eq!(DataType::sniff(&[0, 159, 146, 150]), DataType::Binary);
+        assert_eq!(DataType::sniff(b""), DataType::Unknown);
+    }
+
+    #[test]
+    fn test_save_durable_leaves_no_temp_file() {
+        let dir = tmp_path("durable");
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("event.bin");
+
+        let mut sf = SynapseFile::new(Manifest::new("durable"), b"payload".to_vec());
+        sf.save_durable(&path, true).unwrap();
+        sf.payload = b"replaced".to_vec();
+        sf.save_durable(&path, true).unwrap();
+        let loaded = SynapseFile::load(&path).unwrap();
+        assert_eq!(loaded.payload, b"replaced");
+        assert!(loaded.verify());
+
+        sf.save_durable(&dir.join("split.bin"), false).unwrap();
+        let loaded = SynapseFile::load(&dir.join("split.bin")).unwrap();
+        assert_eq!(loaded.manifest.id, "durable");
+
+        let names: Vec<String> = fs::read_dir(&dir)
+            .unwrap()
+            .map(|e| e.unwrap().file_name().to_string_lossy().to_string())
+            .collect();
+        assert!(names.iter().all(|n| !n.ends_with(".tmp")), "{names:?}");
+        assert_eq!(names.len(), 3);
+
+        // Concurrent writers in one process must not share a temp file.
+        std::thread::scope(|s| {
+            for i in 0..8u8 {
+                let path = &path;
+                s.spawn(move || {
+                    let mut sf = SynapseFile::new(Manifest::new("durable"), vec![i; 4096]);
+                    sf.save_durable(path, true).unwrap();
+                });
+            }
+        });
+        assert!(SynapseFile::load(&path).unwrap().verify());
+        assert_eq!(fs::read_dir(&dir).unwrap().count(), 3);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_manifest_json_roundtrip() {
         let mut m = Manifest::new("abc");
@@ -246,6 +1134,21 @@ mod tests {
         ));
     }
 
+    #[test]
+    fn test_from_bytes_version_policy() {
+        let mut sf = SynapseFile::new(Manifest::new("v2"), b"data".to_vec());
+        let mut bytes = sf.to_bytes().unwrap();
+        bytes[4..6].copy_from_slice(&2u16.to_be_bytes());
+
+        assert!(matches!(
+            SynapseFile::from_bytes(&bytes),
+            Err(ManifestError::UnsupportedVersion { found: 2 })
+        ));
+        let loaded = SynapseFile::from_bytes_allow_newer(&bytes).unwrap();
+        assert_eq!(loaded.manifest.id, "v2");
+        assert_eq!(loaded.payload, b"data");
+    }
+
     #[test]
     fn test_from_bytes_rejects_manifest_size_too_large() {
         let mut buf = Vec::new();
@@ -299,6 +1202,38 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_load_header_only_matches_full_load() {
+        let path = tmp_path("header.syn");
+        let manifest = Manifest::new("header-id")
+            .with_node("edge_1")
+            .with_tag("sensor");
+        let mut sf = SynapseFile::new(manifest, vec![7u8; 4096]);
+        sf.save(&path, true).unwrap();
+
+        let full = SynapseFile::load(&path).unwrap();
+        assert_eq!(SynapseFile::load_header_only(&path).unwrap(), full.manifest);
+
+        let bytes = sf.to_bytes().unwrap();
+        let header_end = bytes.len() - sf.payload.len();
+        assert_eq!(
+            SynapseFile::from_bytes_header_only(&bytes[..header_end]).unwrap(),
+            full.manifest
+        );
+        assert!(matches!(
+            SynapseFile::from_bytes_header_only(&bytes[..header_end - 1]),
+            Err(ManifestError::DataTooSmall)
+        ));
+
+        fs::write(&path, &bytes[..header_end - 1]).unwrap();
+        assert!(matches!(
+            SynapseFile::load_header_only(&path),
+            Err(ManifestError::DataTooSmall)
+        ));
+
+        let _ = fs::remove_file(path);
+    }
+
     #[test]
     fn test_save_load_sidecar_manifest() {
         let path = tmp_path("sidecar.bin");
@@ -331,6 +1266,20 @@ mod tests {
         let _ = fs::remove_file(path);
     }
 
+    #[test]
+    fn test_refresh_hash_after_payload_mutation() {
+        let mut sf = SynapseFile::new(Manifest::new("x"), b"orig".to_vec());
+        sf.refresh_hash();
+        assert!(sf.verify());
+
+        sf.payload.extend_from_slice(b"-more");
+        assert!(!sf.verify());
+        let hash = sf.refresh_hash().to_string();
+        assert_eq!(hash, sf.manifest.data_hash);
+        assert_eq!(sf.manifest.data_size, b"orig-more".len());
+        assert!(sf.verify());
+    }
+
     #[test]
     fn test_verify_false_on_payload_tamper() {
         let mut sf = SynapseFile::new(Manifest::new("x"), b"orig".to_vec());
@@ -339,4 +1288,164 @@ mod tests {
         loaded.payload = b"tampered".to_vec();
         assert!(!loaded.verify());
     }
+
+    #[test]
+    fn test_payload_reader_and_writer() {
+        use std::io::{Read, Write};
+
+        let mut sf = SynapseFile::new(Manifest::new("x"), b"head".to_vec());
+        {
+            let mut writer = sf.payload_writer();
+            writer.write_all(b"-tail").unwrap();
+            write!(writer, "-{}", 42).unwrap();
+        }
+        assert_eq!(sf.payload, b"head-tail-42");
+
+        let mut out = String::new();
+        sf.payload_reader().read_to_string(&mut out).unwrap();
+        assert_eq!(out, "head-tail-42");
+
+        sf.refresh_hash();
+        assert!(sf.verify());
+    }
+
+    #[test]
+    fn test_content_id_ignores_metadata_order() {
+        let a = Manifest::new("x")
+            .with_metadata_entry("unit", "celsius")
+            .with_metadata_entry("sensor", serde_json::json!({"b": 1, "a": 2}));
+        let b = Manifest::new("x")
+            .with_metadata_entry("sensor", serde_json::json!({"a": 2, "b": 1}))
+            .with_metadata_entry("unit", "celsius");
+        let file_a = SynapseFile::new(a, b"data".to_vec());
+        let mut file_b = SynapseFile::new(b, b"data".to_vec());
+        file_b.refresh_hash();
+        assert_eq!(file_a.content_id(), file_b.content_id());
+        assert_eq!(file_a.content_id().len(), 64);
+
+        file_b.payload.push(b'!');
+        assert_ne!(file_a.content_id(), file_b.content_id());
+    }
+
+    #[test]
+    fn test_blake3_hash_algorithm() {
+        let mut manifest = Manifest::new("x");
+        manifest.hash_algorithm = HashAlgorithm::Blake3;
+        let mut sf = SynapseFile::new(manifest, b"payload".to_vec());
+        let bytes = sf.to_bytes().unwrap();
+        assert_eq!(
+            sf.manifest.data_hash,
+            blake3::hash(b"payload").to_hex().as_str()
+        );
+        assert!(
+            sf.manifest
+                .to_json()
+                .unwrap()
+                .contains("\"hash_algorithm\": \"blake3\"")
+        );
+
+        let mut loaded = SynapseFile::from_bytes(&bytes).unwrap();
+        assert_eq!(loaded.manifest.hash_algorithm, HashAlgorithm::Blake3);
+        assert!(loaded.verify());
+        loaded.payload.push(0);
+        assert!(!loaded.verify());
+
+        let flat = sf.manifest.to_flat_map();
+        assert_eq!(Manifest::from_flat_map(&flat).unwrap(), sf.manifest);
+
+        // Manifests written before the field existed default to SHA-256.
+        let legacy = Manifest::from_json(r#"{"id":"old"}"#).unwrap();
+        assert_eq!(legacy.hash_algorithm, HashAlgorithm::Sha256);
+        assert!(!legacy.to_json().unwrap().contains("hash_algorithm"));
+    }
+
+    #[test]
+    fn test_split_and_merge_chunks_round_trip() {
+        let payload: Vec<u8> = (0..1024 * 1024)
+            .map(|i: u32| (i * 31 % 251) as u8)
+            .collect();
+        let mut sf = SynapseFile::for_wid(
+            "20260212T091530.0000Z-a3f91c",
+            4,
+            6,
+            TimeUnit::Sec,
+            payload.clone(),
+        )
+        .unwrap();
+        sf.manifest = sf.manifest.clone().with_tag("raw");
+
+        let mut chunks = sf.split_into_chunks(64 * 1024).unwrap();
+        assert_eq!(chunks.len(), 16);
+        for (i, chunk) in chunks.iter().enumerate() {
+            assert!(chunk.verify());
+            assert_eq!(chunk.payload.len(), 64 * 1024);
+            assert_eq!(
+                chunk.manifest.id,
+                format!("20260212T091530.0000Z-a3f91c#{i}")
+            );
+            assert_eq!(chunk.manifest.metadata[CHUNK_INDEX_KEY], i);
+            assert_eq!(chunk.manifest.metadata[CHUNK_TOTAL_KEY], 16);
+            assert_eq!(
+                chunk.manifest.metadata[PARENT_ID_KEY],
+                sf.manifest.id.as_str()
+            );
+        }
+
+        // Chunks survive a trip through the wire format, in any order.
+        chunks.reverse();
+        let chunks: Vec<SynapseFile> = chunks
+            .iter_mut()
+            .map(|chunk| SynapseFile::from_bytes(&chunk.to_bytes().unwrap()).unwrap())
+            .collect();
+        let merged = SynapseFile::merge_chunks(chunks).unwrap();
+        assert_eq!(merged.payload, payload);
+        assert_eq!(merged.manifest, sf.manifest);
+        assert!(merged.verify());
+
+        let mut small = SynapseFile::new(Manifest::new("small"), b"abcde".to_vec());
+        let parts = small.split_into_chunks(2).unwrap();
+        assert_eq!(
+            parts.iter().map(|c| c.payload.len()).collect::<Vec<_>>(),
+            [2, 2, 1]
+        );
+        let mut empty = SynapseFile::new(Manifest::new("empty"), Vec::new());
+        let parts = empty.split_into_chunks(2).unwrap();
+        assert_eq!(parts.len(), 1);
+        assert!(SynapseFile::merge_chunks(parts).unwrap().payload.is_empty());
+    }
+
+    #[test]
+    fn test_merge_chunks_rejects_bad_sets() {
+        let mut sf = SynapseFile::new(Manifest::new("parent"), b"0123456789".to_vec());
+        let chunks = sf.split_into_chunks(4).unwrap();
+        let rejected = |chunks: Vec<SynapseFile>| {
+            matches!(
+                SynapseFile::merge_chunks(chunks),
+                Err(ManifestError::InvalidChunks(_))
+            )
+        };
+
+        assert!(rejected(Vec::new()));
+        assert!(rejected(chunks[..2].to_vec()));
+
+        let mut dup = chunks.clone();
+        dup[2] = dup[1].clone();
+        assert!(rejected(dup));
+
+        let mut tampered = chunks.clone();
+        tampered[1].payload[0] ^= 1;
+        assert!(rejected(tampered));
+
+        let mut foreign = chunks.clone();
+        foreign[0]
+            .manifest
+            .metadata
+            .insert(PARENT_ID_KEY.to_string(), "other".into());
+        assert!(rejected(foreign));
+
+        assert!(matches!(
+            sf.split_into_chunks(0),
+            Err(ManifestError::InvalidChunks(_))
+        ));
+    }
 }
@@ -0,0 +1,169 @@
+//! Arrival-rate anomaly detection over a sliding window of WID timestamps.
+
+use std::collections::VecDeque;
+use std::time::Duration;
+
+use chrono::{DateTime, Utc};
+
+use crate::wid::{TimeUnit, WidError, parse_timestamp};
+
+/// Flags sudden changes in how many IDs arrive per second.
+///
+/// Arrivals are bucketed by the whole second of each WID's own timestamp (not
+/// the time `observe` is called), so replayed or delayed IDs are counted where
+/// they belong. The newest second is compared against the mean and standard
+/// deviation of the earlier seconds in the window; it may still be filling, so
+/// check after it has closed to avoid flagging a partial second as a drop.
+#[derive(Debug, Clone)]
+pub struct RateMonitor {
+    window_secs: i64,
+    /// `(unix second, arrivals)`, oldest first, at most one entry per second.
+    buckets: VecDeque<(i64, u64)>,
+}
+
+impl RateMonitor {
+    /// Track the last `window` of arrivals, rounded up to whole seconds.
+    pub fn new(window: Duration) -> Self {
+        let window_secs = window
+            .as_secs()
+            .saturating_add(u64::from(window.subsec_nanos() > 0))
+            .max(1);
+        Self {
+            window_secs: i64::try_from(window_secs).unwrap_or(i64::MAX),
+            buckets: VecDeque::new(),
+        }
+    }
+
+    /// Record one arrival. Any WID or HLC-WID timestamp precision (`sec`, `ms`
+    /// or `ns`) is accepted; only the timestamp is parsed. IDs older than the
+    /// window are ignored.
+    pub fn observe(&mut self, wid: &str) -> Result<(), WidError> {
+        let sec = wid_timestamp(wid)?.timestamp();
+        if let Some(&(newest, _)) = self.buckets.back()
+            && sec <= newest.saturating_sub(self.window_secs)
+        {
+            return Ok(());
+        }
+        match self.buckets.iter().rposition(|&(s, _)| s <= sec) {
+            Some(i) if self.buckets[i].0 == sec => self.buckets[i].1 += 1,
+            Some(i) => self.buckets.insert(i + 1, (sec, 1)),
+            None => self.buckets.push_front((sec, 1)),
+        }
+        let newest = self.buckets.back().map_or(sec, |&(s, _)| s);
+        while self
+            .buckets
+            .front()
+            .is_some_and(|&(s, _)| s <= newest.saturating_sub(self.window_secs))
+        {
+            self.buckets.pop_front();
+        }
+        Ok(())
+    }
+
+    /// Whether the newest second's count is more than `z_threshold` standard
+    /// deviations from the mean of the preceding seconds in the window.
+    ///
+    /// Seconds without arrivals count as zero once the first arrival has been
+    /// seen. The deviation is floored at one ID per second so a perfectly
+    /// steady history does not flag a one-ID wobble. Needs at least two
+    /// earlier seconds of history; returns `false` until then.
+    pub fn is_anomalous(&self, z_threshold: f64) -> bool {
+        let (Some(&(first, _)), Some(&(newest, recent))) =
+            (self.buckets.front(), self.buckets.back())
+        else {
+            return false;
+        };
+        let start = first.max(newest.saturating_sub(self.window_secs) + 1);
+        let n = (newest - start) as f64;
+        if n < 2.0 {
+            return false;
+        }
+        let history = || {
+            self.buckets
+                .iter()
+                .filter(|&&(s, _)| s >= start && s < newest)
+                .map(|&(_, c)| c as f64)
+        };
+        let mean = history().sum::<f64>() / n;
+        // Seconds with no bucket contribute (0 - mean)^2 each.
+        let empty = n - history().count() as f64;
+        let variance =
+            (history().map(|c| (c - mean).powi(2)).sum::<f64>() + empty * mean.powi(2)) / n;
+        let stddev = variance.sqrt().max(1.0);
+        (recent as f64 - mean).abs() > z_threshold * stddev
+    }
+}
+
+/// Timestamp of a WID-shaped string, inferring the time unit from the width
+/// of the time field.
+fn wid_timestamp(wid: &str) -> Result<DateTime<Utc>, WidError> {
+    let invalid = || WidError::InvalidFormat(wid.to_string());
+    let (date, time) = wid
+        .split_once('.')
+        .and_then(|(ts, _)| ts.split_once('T'))
+        .ok_or_else(invalid)?;
+    let time_unit = [TimeUnit::Sec, TimeUnit::Ms, TimeUnit::Ns]
+        .into_iter()
+        .find(|unit| unit.time_digits() == time.len())
+        .ok_or_else(invalid)?;
+    if date.len() != 8 || !date.bytes().chain(time.bytes()).all(|b| b.is_ascii_digit()) {
+        return Err(invalid());
+    }
+    parse_timestamp(time_unit, date, time).ok_or(WidError::InvalidTimestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn feed(monitor: &mut RateMonitor, sec: u32, count: usize) {
+        for seq in 0..count {
+            let id = format!("20260212T0915{sec:02}.{seq:04}Z-a3f91c");
+            monitor.observe(&id).unwrap();
+        }
+    }
+
+    #[test]
+    fn test_steady_rate_then_burst() {
+        let mut monitor = RateMonitor::new(Duration::from_secs(30));
+        for sec in 0..20 {
+            feed(&mut monitor, sec, 10 + (sec as usize % 2));
+        }
+        assert!(!monitor.is_anomalous(3.0));
+
+        feed(&mut monitor, 20, 100);
+        assert!(monitor.is_anomalous(3.0));
+        assert!(!monitor.is_anomalous(1000.0));
+    }
+
+    #[test]
+    fn test_observe_accepts_units_and_rejects_garbage() {
+        let mut monitor = RateMonitor::new(Duration::from_millis(1500));
+        assert_eq!(monitor.window_secs, 2);
+        monitor.observe("20260212T091530123.0000Z").unwrap();
+        monitor
+            .observe("20260212T091530123456789.0000Z-node01")
+            .unwrap();
+        monitor
+            .observe("20260212T091530.0000Z-node01-abcd")
+            .unwrap();
+        assert_eq!(monitor.buckets, [(1_770_887_730, 3)]);
+        assert!(!monitor.is_anomalous(0.0));
+
+        assert!(monitor.observe("not a wid").is_err());
+        assert!(monitor.observe("20261312T091530.0000Z").is_err());
+    }
+
+    #[test]
+    fn test_huge_windows_keep_every_arrival() {
+        for window in [Duration::MAX, Duration::from_secs(u64::MAX)] {
+            let mut monitor = RateMonitor::new(window);
+            assert_eq!(monitor.window_secs, i64::MAX);
+            for sec in 0..5 {
+                feed(&mut monitor, sec, 2);
+            }
+            assert_eq!(monitor.buckets.len(), 5);
+            assert!(!monitor.is_anomalous(3.0));
+        }
+    }
+}
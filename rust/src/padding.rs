@@ -0,0 +1,165 @@
+//! Padding sources for the random `-<pad>` suffix of generated IDs.
+
+use rand::{Rng, RngExt};
+
+/// Lowercase hex, the alphabet the WID grammar allows in padding.
+pub const HEX_ALPHABET: &[u8] = b"0123456789abcdef";
+
+/// Source of padding characters, e.g. a hardware RNG or a deterministic
+/// derivation.
+///
+/// Implementations must append exactly `z` characters drawn from `alphabet`;
+/// anything else produces IDs that no longer validate. `Send + Sync` keeps
+/// `WidGen`, which boxes its provider, shareable across threads.
+pub trait PaddingProvider: Send + Sync {
+    fn fill(&mut self, out: &mut String, z: usize, alphabet: &[u8]);
+}
+
+impl<P: PaddingProvider + ?Sized> PaddingProvider for &mut P {
+    fn fill(&mut self, out: &mut String, z: usize, alphabet: &[u8]) {
+        (**self).fill(out, z, alphabet);
+    }
+}
+
+/// How an RNG-backed provider turns random output into padding characters.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum PaddingMode {
+    /// One bounded draw per character.
+    #[default]
+    PerChar,
+    /// One 64-bit draw per `64 / bits` characters, slicing `bits`-wide
+    /// symbols out of each word. Only alphabets whose length is a power of
+    /// two (such as hex) slice uniformly; others fall back to `PerChar`.
+    Packed,
+}
+
+/// Default provider: uniform random characters from the thread-local RNG.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct HexPadding;
+
+impl PaddingProvider for HexPadding {
+    fn fill(&mut self, out: &mut String, z: usize, alphabet: &[u8]) {
+        fill_from(&mut rand::rng(), out, z, alphabet);
+    }
+}
+
+/// Adapts any RNG into a provider.
+pub(crate) struct RngPadding<R>(pub R);
+
+impl<R: RngExt + Send + Sync> PaddingProvider for RngPadding<R> {
+    fn fill(&mut self, out: &mut String, z: usize, alphabet: &[u8]) {
+        fill_from(&mut self.0, out, z, alphabet);
+    }
+}
+
+/// Like `RngPadding`, in `PaddingMode::Packed`.
+pub(crate) struct PackedPadding<R>(pub R);
+
+impl<R: Rng + Send + Sync> PaddingProvider for PackedPadding<R> {
+    fn fill(&mut self, out: &mut String, z: usize, alphabet: &[u8]) {
+        fill_packed(&mut self.0, out, z, alphabet);
+    }
+}
+
+fn fill_packed(rng: &mut impl Rng, out: &mut String, z: usize, alphabet: &[u8]) {
+    if alphabet.len() < 2 || !alphabet.len().is_power_of_two() {
+        return fill_from(rng, out, z, alphabet);
+    }
+    let bits = alphabet.len().trailing_zeros() as usize;
+    let mask = (1u64 << bits) - 1;
+    let per_word = 64 / bits;
+    let mut remaining = z;
+    while remaining > 0 {
+        let take = per_word.min(remaining);
+        let mut word = rng.next_u64();
+        for _ in 0..take {
+            out.push(alphabet[(word & mask) as usize] as char);
+            word >>= bits;
+        }
+        remaining -= take;
+    }
+}
+
+fn fill_from(rng: &mut impl RngExt, out: &mut String, z: usize, alphabet: &[u8]) {
+    out.extend((0..z).map(|_| alphabet[rng.random_range(0..alphabet.len())] as char));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use rand::rngs::StdRng;
+    use rand::{SeedableRng, TryRng};
+    use std::convert::Infallible;
+
+    /// Wraps an RNG and counts how many words were drawn from it.
+    struct CountingRng {
+        inner: StdRng,
+        calls: usize,
+    }
+
+    impl TryRng for CountingRng {
+        type Error = Infallible;
+
+        fn try_next_u32(&mut self) -> Result<u32, Infallible> {
+            self.calls += 1;
+            Ok(self.inner.next_u32())
+        }
+
+        fn try_next_u64(&mut self) -> Result<u64, Infallible> {
+            self.calls += 1;
+            Ok(self.inner.next_u64())
+        }
+
+        fn try_fill_bytes(&mut self, dst: &mut [u8]) -> Result<(), Infallible> {
+            self.calls += 1;
+            self.inner.fill_bytes(dst);
+            Ok(())
+        }
+    }
+
+    fn counting() -> CountingRng {
+        CountingRng {
+            inner: StdRng::seed_from_u64(7),
+            calls: 0,
+        }
+    }
+
+    #[test]
+    fn test_packed_padding_draws_fewer_words() {
+        let mut per_char_rng = counting();
+        let mut per_char = String::new();
+        fill_from(&mut per_char_rng, &mut per_char, 64, HEX_ALPHABET);
+
+        let mut packed_rng = counting();
+        let mut packed = String::new();
+        fill_packed(&mut packed_rng, &mut packed, 64, HEX_ALPHABET);
+
+        for out in [&per_char, &packed] {
+            assert_eq!(out.len(), 64);
+            assert!(out.bytes().all(|b| HEX_ALPHABET.contains(&b)));
+        }
+        assert!(per_char_rng.calls >= 64);
+        assert_eq!(packed_rng.calls, 4);
+
+        let mut odd = String::new();
+        fill_packed(&mut packed_rng, &mut odd, 17, HEX_ALPHABET);
+        assert_eq!(odd.len(), 17);
+        assert_eq!(packed_rng.calls, 6);
+    }
+
+    #[test]
+    fn test_packed_padding_is_roughly_uniform() {
+        let mut rng = StdRng::seed_from_u64(11);
+        let mut out = String::new();
+        fill_packed(&mut rng, &mut out, 16_000, HEX_ALPHABET);
+        let mut counts = [0usize; 16];
+        for b in out.bytes() {
+            counts[HEX_ALPHABET.iter().position(|&c| c == b).unwrap()] += 1;
+        }
+        // Expected 1000 each; 5 sigma is about 150.
+        assert!(
+            counts.iter().all(|&n| (850..=1150).contains(&n)),
+            "{counts:?}"
+        );
+    }
+}
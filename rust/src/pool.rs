@@ -0,0 +1,125 @@
+//! Pre-generated WIDs served from a background thread.
+//!
+//! A `WidPool` moves the per-call cost of `WidGen::next_wid` (clock read,
+//! formatting, padding) off the hot path. The background thread keeps a
+//! bounded channel full; callers only pay for a channel receive.
+//!
+//! IDs are minted ahead of use, so their timestamps lag the moment they are
+//! taken by up to however long they sat in the channel. With a large
+//! `capacity` and a slow consumer that lag can be significant.
+
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::{self, Receiver};
+use std::thread::{self, JoinHandle};
+
+use crate::wid::{TimeUnit, WidError, WidGen};
+
+/// A bounded buffer of WIDs refilled by a background generator thread.
+///
+/// All IDs come from one `WidGen` and leave the channel in the order they
+/// were minted, so every ID returned by `get` or `get_blocking` validates and
+/// sorts after the ones returned before it.
+pub struct WidPool {
+    rx: Receiver<String>,
+    stop: Arc<AtomicBool>,
+    handle: Option<JoinHandle<()>>,
+}
+
+impl WidPool {
+    /// Start a pool buffering up to `capacity` IDs.
+    ///
+    /// A `capacity` of 0 makes the channel a rendezvous: `get` only succeeds
+    /// while the background thread is waiting to hand an ID over.
+    pub fn new(w: usize, z: usize, time_unit: TimeUnit, capacity: usize) -> Result<Self, WidError> {
+        let mut generator = WidGen::new_with_time_unit(w, z, None, time_unit)?;
+        let (tx, rx) = mpsc::sync_channel(capacity);
+        let stop = Arc::new(AtomicBool::new(false));
+        let flag = Arc::clone(&stop);
+        let handle = thread::spawn(move || {
+            while !flag.load(Ordering::Acquire) {
+                if tx.send(generator.next_wid()).is_err() {
+                    break;
+                }
+            }
+        });
+        Ok(Self {
+            rx,
+            stop,
+            handle: Some(handle),
+        })
+    }
+
+    /// Take a buffered ID, or `None` if the pool is momentarily empty.
+    pub fn get(&self) -> Option<String> {
+        self.rx.try_recv().ok()
+    }
+
+    /// Take an ID, waiting for the background thread if the pool is empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the background thread has died.
+    pub fn get_blocking(&self) -> String {
+        self.rx.recv().expect("WidPool background thread stopped")
+    }
+
+    /// Stop the background thread and wait for it to exit. Buffered IDs are
+    /// discarded.
+    pub fn close(mut self) {
+        self.stop.store(true, Ordering::Release);
+        // Draining unblocks a pending send; the thread then sees the flag,
+        // exits and drops its sender, which ends the loop.
+        while self.rx.recv().is_ok() {}
+        if let Some(handle) = self.handle.take() {
+            let _ = handle.join();
+        }
+    }
+}
+
+impl Drop for WidPool {
+    /// Signal the thread without joining; dropping the receiver right after
+    /// fails its pending send, so it exits on its own.
+    fn drop(&mut self) {
+        self.stop.store(true, Ordering::Release);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wid::validate_wid;
+
+    #[test]
+    fn test_pool_serves_valid_monotonic_ids() {
+        let pool = WidPool::new(4, 6, TimeUnit::Sec, 16).unwrap();
+        let ids: Vec<String> = (0..200).map(|_| pool.get_blocking()).collect();
+        assert!(ids.iter().all(|id| validate_wid(id, 4, 6)));
+        assert!(ids.windows(2).all(|p| p[0] < p[1]));
+
+        let next = loop {
+            if let Some(id) = pool.get() {
+                break id;
+            }
+            thread::yield_now();
+        };
+        assert!(next > ids[ids.len() - 1]);
+        pool.close();
+    }
+
+    #[test]
+    fn test_pool_rejects_invalid_params_and_closes_promptly() {
+        assert!(matches!(
+            WidPool::new(4, 99, TimeUnit::Sec, 8),
+            Err(WidError::InvalidZ)
+        ));
+
+        for capacity in [0, 1, 64] {
+            let pool = WidPool::new(4, 0, TimeUnit::Ms, capacity).unwrap();
+            pool.get_blocking();
+            pool.close();
+        }
+        // Dropping without `close` must not hang either.
+        drop(WidPool::new(4, 0, TimeUnit::Sec, 4).unwrap());
+    }
+}
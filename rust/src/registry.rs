@@ -0,0 +1,92 @@
+//! Opt-in, process-wide record of issued IDs for `next_unique_wid`.
+//!
+//! Distinct generators can in principle emit the same ID (same tick and
+//! sequence, colliding padding). Once enabled, every ID handed out through a
+//! `next_unique_wid` call is remembered for the rest of the process, so such a
+//! collision is reported instead of silently returned.
+//!
+//! Memory cost: every issued ID is kept until the process exits, roughly its
+//! length plus ~50 bytes of `String` and hash-table overhead, so a million
+//! `W=4, Z=6` IDs cost on the order of 80 MB. Only enable it for bounded
+//! workloads.
+
+use std::collections::HashSet;
+use std::sync::Mutex;
+
+use once_cell::sync::OnceCell;
+
+use crate::wid::WidError;
+
+static ISSUED: OnceCell<Mutex<HashSet<String>>> = OnceCell::new();
+
+/// Turn on the global issued-ID registry. Idempotent; there is no way to turn
+/// it off again.
+pub fn enable_global_uniqueness() {
+    ISSUED.get_or_init(|| Mutex::new(HashSet::new()));
+}
+
+/// Record `id` as issued. A no-op while the registry is disabled.
+pub(crate) fn claim(id: &str) -> Result<(), WidError> {
+    let Some(issued) = ISSUED.get() else {
+        return Ok(());
+    };
+    let mut issued = issued.lock().unwrap_or_else(|e| e.into_inner());
+    if issued.insert(id.to_string()) {
+        Ok(())
+    } else {
+        Err(WidError::DuplicateIssued(id.to_string()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{HLCWidGen, TimeUnit, WidGen, WidGenBuilder};
+    use std::thread;
+
+    #[test]
+    fn test_global_uniqueness_across_threads() {
+        enable_global_uniqueness();
+        enable_global_uniqueness();
+
+        let handles: Vec<_> = (0..4)
+            .map(|_| {
+                thread::spawn(|| {
+                    let mut generator =
+                        WidGen::new_with_time_unit(6, 6, None, TimeUnit::Ms).unwrap();
+                    (0..500)
+                        .map(|_| generator.next_unique_wid().unwrap())
+                        .collect::<Vec<_>>()
+                })
+            })
+            .collect();
+        let all: Vec<String> = handles
+            .into_iter()
+            .flat_map(|h| h.join().unwrap())
+            .collect();
+        let distinct: HashSet<_> = all.iter().collect();
+        assert_eq!(distinct.len(), all.len());
+
+        // Two identically seeded generators at the same slot collide.
+        let tick = 32_503_680_000; // 3000-01-01, ahead of the clock
+        let mut a = WidGenBuilder::new().seed(7).build().unwrap();
+        let mut b = WidGenBuilder::new().seed(7).build().unwrap();
+        a.restore_state(tick, 0);
+        b.restore_state(tick, 0);
+        let first = a.next_unique_wid().unwrap();
+        match b.next_unique_wid() {
+            Err(WidError::DuplicateIssued(id)) => assert_eq!(id, first),
+            other => panic!("expected DuplicateIssued, got {other:?}"),
+        }
+
+        let mut h1 = HLCWidGen::new("reg_node".to_string(), 4, 0).unwrap();
+        let mut h2 = HLCWidGen::new("reg_node".to_string(), 4, 0).unwrap();
+        h1.restore_state(tick, 0).unwrap();
+        h2.restore_state(tick, 0).unwrap();
+        h1.next_unique_hlc_wid().unwrap();
+        assert!(matches!(
+            h2.next_unique_hlc_wid(),
+            Err(WidError::DuplicateIssued(_))
+        ));
+    }
+}
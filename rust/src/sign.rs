@@ -0,0 +1,132 @@
+//! Ed25519 signatures over WIDs, shared with the `A=sign` / `A=verify` CLI.
+
+use base64::Engine as _;
+use base64::engine::general_purpose::URL_SAFE_NO_PAD;
+use ed25519_dalek::pkcs8::{DecodePrivateKey, DecodePublicKey};
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+
+use crate::wid::WidError;
+
+/// Build the canonical signed message:
+/// `"wid-sig-v1:" || len(WID) || ":" || WID || DATA`.
+///
+/// The domain-separation prefix and the explicit WID byte-length frame the
+/// WID/DATA boundary so no bytes can shift between them (a plain `WID || DATA`
+/// concatenation is ambiguous).
+pub fn signed_message(wid: &str, data: Option<&[u8]>) -> Vec<u8> {
+    let wid = wid.as_bytes();
+    let mut msg = format!("wid-sig-v1:{}:", wid.len()).into_bytes();
+    msg.extend_from_slice(wid);
+    msg.extend_from_slice(data.unwrap_or_default());
+    msg
+}
+
+/// Sign `wid` (and optional `data`), returning the unpadded base64url
+/// signature.
+///
+/// `private_key` is a 32-byte Ed25519 seed, or a PKCS#8 key as PEM or DER.
+pub fn sign_wid(wid: &str, data: Option<&[u8]>, private_key: &[u8]) -> Result<String, WidError> {
+    let key = signing_key(private_key)?;
+    let sig: Signature = key.sign(&signed_message(wid, data));
+    Ok(URL_SAFE_NO_PAD.encode(sig.to_bytes()))
+}
+
+/// Verify a base64url signature (padding optional) made by `sign_wid` or
+/// `A=sign`.
+///
+/// `public_key` is a 32-byte Ed25519 key, or an SPKI key as PEM or DER.
+/// Returns `Ok(false)` for a well-formed signature that does not match, and an
+/// error when the key or signature cannot be decoded.
+pub fn verify_signed(
+    wid: &str,
+    data: Option<&[u8]>,
+    signature_b64url: &str,
+    public_key: &[u8],
+) -> Result<bool, WidError> {
+    let key = verifying_key(public_key)?;
+    let sig_bytes = URL_SAFE_NO_PAD
+        .decode(signature_b64url.trim().trim_end_matches('='))
+        .map_err(|_| WidError::InvalidSignature)?;
+    let sig = Signature::from_slice(&sig_bytes).map_err(|_| WidError::InvalidSignature)?;
+    Ok(key.verify_strict(&signed_message(wid, data), &sig).is_ok())
+}
+
+fn signing_key(bytes: &[u8]) -> Result<SigningKey, WidError> {
+    if let Ok(seed) = <&[u8; 32]>::try_from(bytes) {
+        return Ok(SigningKey::from_bytes(seed));
+    }
+    if let Ok(pem) = std::str::from_utf8(bytes)
+        && let Ok(key) = SigningKey::from_pkcs8_pem(pem)
+    {
+        return Ok(key);
+    }
+    SigningKey::from_pkcs8_der(bytes).map_err(|_| WidError::InvalidKey)
+}
+
+fn verifying_key(bytes: &[u8]) -> Result<VerifyingKey, WidError> {
+    if let Ok(raw) = <&[u8; 32]>::try_from(bytes) {
+        return VerifyingKey::from_bytes(raw).map_err(|_| WidError::InvalidKey);
+    }
+    if let Ok(pem) = std::str::from_utf8(bytes)
+        && let Ok(key) = VerifyingKey::from_public_key_pem(pem)
+    {
+        return Ok(key);
+    }
+    VerifyingKey::from_public_key_der(bytes).map_err(|_| WidError::InvalidKey)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ed25519_dalek::pkcs8::spki::der::pem::LineEnding;
+    use ed25519_dalek::pkcs8::{EncodePrivateKey, EncodePublicKey};
+
+    const WID: &str = "20260212T091530.0000Z-a3f91c";
+
+    #[test]
+    fn test_sign_and_verify_round_trip() {
+        let seed = [7u8; 32];
+        let public = SigningKey::from_bytes(&seed).verifying_key().to_bytes();
+
+        let sig = sign_wid(WID, Some(b"payload"), &seed).unwrap();
+        assert!(verify_signed(WID, Some(b"payload"), &sig, &public).unwrap());
+        assert!(verify_signed(WID, Some(b"payload"), &format!("{sig}=="), &public).unwrap());
+
+        // PEM keys, as used by the CLI.
+        let key = SigningKey::from_bytes(&seed);
+        let private_pem = key.to_pkcs8_pem(LineEnding::LF).unwrap();
+        let public_pem = key
+            .verifying_key()
+            .to_public_key_pem(LineEnding::LF)
+            .unwrap();
+        let pem_sig = sign_wid(WID, None, private_pem.as_bytes()).unwrap();
+        assert!(verify_signed(WID, None, &pem_sig, public_pem.as_bytes()).unwrap());
+        assert!(verify_signed(WID, None, &pem_sig, &public).unwrap());
+    }
+
+    #[test]
+    fn test_verify_detects_tampering() {
+        let seed = [7u8; 32];
+        let public = SigningKey::from_bytes(&seed).verifying_key().to_bytes();
+        let sig = sign_wid(WID, Some(b"payload"), &seed).unwrap();
+
+        let other = "20260212T091530.0001Z-a3f91c";
+        assert!(!verify_signed(other, Some(b"payload"), &sig, &public).unwrap());
+        assert!(!verify_signed(WID, Some(b"payloaD"), &sig, &public).unwrap());
+        assert!(!verify_signed(WID, None, &sig, &public).unwrap());
+        // Moving bytes across the WID/DATA boundary changes the message.
+        assert_ne!(
+            signed_message("ab", Some(b"c")),
+            signed_message("a", Some(b"bc"))
+        );
+
+        assert!(matches!(
+            verify_signed(WID, None, "not base64!", &public),
+            Err(WidError::InvalidSignature)
+        ));
+        assert!(matches!(
+            verify_signed(WID, None, &sig, b"short"),
+            Err(WidError::InvalidKey)
+        ));
+    }
+}
@@ -0,0 +1,116 @@
+//! Line-oriented ID streaming with progress reporting, as behind `wid stream`.
+
+use std::io::{self, Write};
+
+/// How `run_stream_with_progress` paces its output.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct StreamOpts {
+    /// IDs to write; 0 streams until the generator or the sink gives out.
+    pub count: usize,
+    /// Flush the sink after this many IDs (1 flushes every line, 0 only at
+    /// the end).
+    pub flush_every: usize,
+    /// Call the progress callback after this many IDs; 0 never calls it.
+    pub progress_every: usize,
+}
+
+impl Default for StreamOpts {
+    /// Unbounded, flushed every line, without progress callbacks.
+    fn default() -> Self {
+        Self {
+            count: 0,
+            flush_every: 1,
+            progress_every: 0,
+        }
+    }
+}
+
+/// Write IDs from `ids` to `out`, one per line, as `opts` directs.
+///
+/// `on_progress` receives the running total after every
+/// `opts.progress_every` IDs. `out` is flushed once more before returning,
+/// also when `ids` ends early. Returns the number of IDs written.
+///
+/// ```
+/// use wid::{StreamOpts, WidGen, run_stream_with_progress};
+///
+/// let opts = StreamOpts { count: 10, progress_every: 5, ..StreamOpts::default() };
+/// let mut out = Vec::new();
+/// let mut seen = Vec::new();
+/// let ids = WidGen::new(4, 6, None).unwrap();
+/// let n = run_stream_with_progress(ids, &opts, &mut out, |n| seen.push(n)).unwrap();
+/// assert_eq!((n, seen), (10, vec![5, 10]));
+/// ```
+pub fn run_stream_with_progress(
+    ids: impl IntoIterator<Item = String>,
+    opts: &StreamOpts,
+    out: &mut impl Write,
+    mut on_progress: impl FnMut(usize),
+) -> io::Result<usize> {
+    let mut ids = ids.into_iter();
+    let mut emitted = 0usize;
+    while opts.count == 0 || emitted < opts.count {
+        let Some(id) = ids.next() else {
+            break;
+        };
+        writeln!(out, "{id}")?;
+        emitted += 1;
+        if opts.flush_every > 0 && emitted.is_multiple_of(opts.flush_every) {
+            out.flush()?;
+        }
+        if opts.progress_every > 0 && emitted.is_multiple_of(opts.progress_every) {
+            on_progress(emitted);
+        }
+    }
+    out.flush()?;
+    Ok(emitted)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::wid::WidGen;
+    use std::io::BufWriter;
+
+    #[test]
+    fn test_stream_progress_interval() {
+        let opts = StreamOpts {
+            count: 1000,
+            progress_every: 100,
+            ..StreamOpts::default()
+        };
+        let mut out = Vec::new();
+        let mut calls = Vec::new();
+        let ids = WidGen::default_params();
+        let emitted = run_stream_with_progress(ids, &opts, &mut out, |n| calls.push(n)).unwrap();
+        assert_eq!(emitted, 1000);
+        assert_eq!(calls, (1..=10).map(|i| i * 100).collect::<Vec<_>>());
+        assert_eq!(String::from_utf8(out).unwrap().lines().count(), 1000);
+    }
+
+    #[test]
+    fn test_stream_buffered_flushes_on_completion() {
+        let opts = StreamOpts {
+            count: 1000,
+            flush_every: 300,
+            ..StreamOpts::default()
+        };
+        // Larger than the output, so only the explicit flushes reach `inner`.
+        let mut out = BufWriter::with_capacity(1 << 20, Vec::new());
+        let ids = WidGen::default_params();
+        let emitted = run_stream_with_progress(ids, &opts, &mut out, |_| {}).unwrap();
+        assert_eq!(emitted, 1000);
+        assert!(out.buffer().is_empty());
+        let text = String::from_utf8(out.into_inner().unwrap()).unwrap();
+        assert_eq!(text.lines().count(), 1000);
+
+        // A finite source ends the stream early.
+        let mut out = Vec::new();
+        let ids = ["a".to_string(), "b".to_string()];
+        assert_eq!(
+            run_stream_with_progress(ids, &opts, &mut out, |_| {}).unwrap(),
+            2
+        );
+        assert_eq!(out, b"a\nb\n");
+    }
+}
@@ -5,22 +5,55 @@
 //! The generator implements `Iterator<Item = String>`.
 //! Use `next_wid()` for the explicit domain API.
 
-use chrono::{DateTime, TimeZone, Timelike, Utc};
+use chrono::{DateTime, Datelike, NaiveDate, TimeZone, Timelike, Utc};
 use once_cell::sync::Lazy;
-use rand::random_range;
+use rand::SeedableRng;
+use rand::rngs::StdRng;
 use regex::Regex;
-use std::time::{SystemTime, UNIX_EPOCH};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicI64, Ordering as AtomicOrdering};
+use std::sync::{Arc, Mutex};
+use std::thread::ThreadId;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
+use crate::layout::{CanonicalFormat, WidFormat, WidFormatParser};
+use crate::manifest::{GENERATOR_TIME_UNIT_KEY, GENERATOR_W_KEY, GENERATOR_Z_KEY, Manifest};
+use crate::padding::{
+    HEX_ALPHABET, HexPadding, PackedPadding, PaddingMode, PaddingProvider, RngPadding,
+};
+
 /// Maximum sequence/logical-counter width. `10^18 - 1` is the largest
 /// all-nines sequence that fits in an `i64` (`10^19` overflows), so `W > 18`
 /// cannot be represented by the i64-based implementations and is rejected
 /// uniformly across all six languages.
-pub const MAX_W: usize = 18;
+pub const MAX_W: usize = i64::MAX.ilog10() as usize;
+/// Reject a generator width of zero, or one whose `10^w - 1` maximum sequence
+/// does not fit in an `i64`.
+pub(crate) fn check_generator_w(w: usize) -> Result<(), WidError> {
+    if w == 0 {
+        return Err(WidError::InvalidW);
+    }
+    if w > MAX_W {
+        return Err(WidError::WTooLarge {
+            max_supported: MAX_W,
+        });
+    }
+    Ok(())
+}
+
+/// Identifier of the textual WID grammar this crate implements, reported by
+/// `A=version` for compatibility negotiation.
+pub const WID_FORMAT: &str = "WID/1";
 /// Maximum padding width in hex chars, shared with the C implementation's
 /// `WID_MAX_Z` so every implementation accepts and produces the same range.
 pub const MAX_Z: usize = 64;
 
+/// Weight of the newest per-tick sample in `WidGen::throughput_per_sec`.
+const THROUGHPUT_ALPHA: f64 = 0.2;
+
 /// Errors that can occur during WID operations.
 #[derive(Error, Debug)]
 pub enum WidError {
@@ -34,17 +67,66 @@ pub enum WidError {
     InvalidNode,
     #[error("Invalid remote clock values")]
     InvalidRemoteClock,
+    #[error("Empty WID input")]
+    Empty,
     #[error("Invalid WID format: {0}")]
     InvalidFormat(String),
     #[error("Invalid timestamp in WID")]
     InvalidTimestamp,
+    #[error("ID {0} was already issued in this process")]
+    DuplicateIssued(String),
+    #[error("W is too large: at most {max_supported} sequence digits fit in an i64")]
+    WTooLarge { max_supported: usize },
+    #[error("OTP digits must be between 4 and 10, got {0}")]
+    InvalidOtpDigits(usize),
+    #[error("Frame ended before its declared contents")]
+    TruncatedFrame,
+    #[error("Manifest generator config is missing or invalid: {0}")]
+    InvalidManifestConfig(String),
+    #[error("Invalid Ed25519 key")]
+    InvalidKey,
+    #[error("Invalid signature encoding")]
+    InvalidSignature,
+    #[error("Version tag {0} does not fit in one hex digit")]
+    InvalidVersionTag(u8),
+    #[error("Timestamp year {year} outside {min}..={max}")]
+    TimestampOutOfRange { year: i32, min: i32, max: i32 },
+    #[error("Invalid timestamp field length: expected {expected}, found {found}")]
+    InvalidTimestampLength { expected: usize, found: usize },
+    #[error("Unexpected padding segment for Z=0: {0}")]
+    UnexpectedPadding(String),
+    #[error("Sequence {seq} exceeds the maximum {max} for this W")]
+    SequenceOutOfRange { seq: u64, max: u64 },
+    #[error("Shard {shard} does not fit in {shard_digits} digit(s) below W")]
+    InvalidShard { shard: u32, shard_digits: usize },
+    #[error("HLC physical time drifted {drift:?} from the clock, over the {max:?} limit")]
+    ClockDrift { drift: Duration, max: Duration },
+    #[error("Reservation of {0} IDs runs past the last representable slot")]
+    ReservationOutOfRange(usize),
+    #[error("Invalid max node length {0}: must be between 1 and {limit}", limit = crate::hlc::MAX_NODE_LEN_LIMIT)]
+    InvalidMaxNodeLen(usize),
 }
 
+/// Last second that still formats with a four-digit year (9999-12-31T23:59:59Z).
+///
+/// Descending-mode WIDs write `MAX_TICK_SEC - tick` in `sec` mode.
+pub const MAX_TICK_SEC: i64 = 253_402_300_799;
+/// Last millisecond with a four-digit year; the `ms` counterpart of `MAX_TICK_SEC`.
+pub const MAX_TICK_MS: i64 = MAX_TICK_SEC * 1000 + 999;
+/// Last representable nanosecond tick (2262-04-11T23:47:16.854775807Z).
+///
+/// `ns` ticks are `i64` nanoseconds since the epoch, so unlike the other units
+/// `ns` mode cannot reach year 9999.
+pub const MAX_TICK_NS: i64 = i64::MAX;
+
 /// Timestamp precision mode.
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(rename_all = "lowercase"))]
 pub enum TimeUnit {
     Sec,
     Ms,
+    Ns,
 }
 
 impl TimeUnit {
@@ -52,6 +134,7 @@ impl TimeUnit {
         match self {
             Self::Sec => "sec",
             Self::Ms => "ms",
+            Self::Ns => "ns",
         }
     }
 
@@ -59,17 +142,146 @@ impl TimeUnit {
         match s {
             "sec" => Some(Self::Sec),
             "ms" => Some(Self::Ms),
+            "ns" => Some(Self::Ns),
             _ => None,
         }
     }
+
+    /// Largest tick that still formats as a valid timestamp in this unit.
+    fn max_tick(self) -> i64 {
+        match self {
+            TimeUnit::Sec => MAX_TICK_SEC,
+            TimeUnit::Ms => MAX_TICK_MS,
+            TimeUnit::Ns => MAX_TICK_NS,
+        }
+    }
+
+    /// Number of digits in the time-of-day part of the timestamp.
+    pub(crate) fn time_digits(self) -> usize {
+        match self {
+            Self::Sec => 6,
+            Self::Ms => 9,
+            Self::Ns => 15,
+        }
+    }
+
+    pub(crate) fn ticks_per_sec(self) -> i64 {
+        match self {
+            Self::Sec => 1,
+            Self::Ms => 1_000,
+            Self::Ns => 1_000_000_000,
+        }
+    }
+
+    /// Length of `ticks` ticks in this unit.
+    pub(crate) fn duration_of_ticks(self, ticks: u64) -> Duration {
+        match self {
+            Self::Sec => Duration::from_secs(ticks),
+            Self::Ms => Duration::from_millis(ticks),
+            Self::Ns => Duration::from_nanos(ticks),
+        }
+    }
+
+    /// Tick of `ts` in this unit, truncating finer precision. `None` when an
+    /// `ns` tick would overflow `i64`.
+    pub(crate) fn tick_of(self, ts: DateTime<Utc>) -> Option<i64> {
+        match self {
+            Self::Sec => Some(ts.timestamp()),
+            Self::Ms => Some(ts.timestamp_millis()),
+            Self::Ns => ts.timestamp_nanos_opt(),
+        }
+    }
+
+    pub(crate) fn datetime_of(self, tick: i64) -> Option<DateTime<Utc>> {
+        match self {
+            Self::Sec => DateTime::from_timestamp(tick, 0),
+            Self::Ms => DateTime::from_timestamp_millis(tick),
+            Self::Ns => Some(DateTime::from_timestamp_nanos(tick)),
+        }
+    }
+
+    /// Current wall-clock tick; negative if the system clock is set before
+    /// the epoch.
+    pub(crate) fn now_tick(self) -> i64 {
+        let (dur, sign) = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(dur) => (dur, 1),
+            Err(before) => (before.duration(), -1),
+        };
+        sign * match self {
+            Self::Sec => dur.as_secs() as i64,
+            Self::Ms => dur.as_millis() as i64,
+            Self::Ns => dur.as_nanos() as i64,
+        }
+    }
+}
+
+/// Source of the current tick for `WidGen` and `HLCWidGen`.
+///
+/// Ticks must be in the generator's time unit. Generators never move
+/// backwards, so a clock that jumps back is clamped to the last issued tick.
+/// Like `PaddingProvider`, it is `Send + Sync` so generators stay shareable.
+pub trait Clock: Send + Sync {
+    fn now_tick(&self) -> i64;
+}
+
+/// The wall clock, read via `SystemTime` in the given unit. Used by every
+/// constructor that does not take a clock.
+#[derive(Debug, Clone, Copy)]
+pub struct SystemClock(pub TimeUnit);
+
+impl Clock for SystemClock {
+    fn now_tick(&self) -> i64 {
+        self.0.now_tick()
+    }
+}
+
+/// A clock that only moves when told to, for deterministic tests.
+///
+/// Share it via `Arc` to keep a handle after giving it to a generator.
+#[derive(Debug, Default)]
+pub struct ManualClock {
+    tick: AtomicI64,
+}
+
+impl ManualClock {
+    pub fn new(tick: i64) -> Self {
+        Self {
+            tick: AtomicI64::new(tick),
+        }
+    }
+
+    pub fn set(&self, tick: i64) {
+        self.tick.store(tick, AtomicOrdering::SeqCst);
+    }
+
+    pub fn advance(&self, ticks: i64) {
+        self.tick.fetch_add(ticks, AtomicOrdering::SeqCst);
+    }
+}
+
+impl Clock for ManualClock {
+    fn now_tick(&self) -> i64 {
+        self.tick.load(AtomicOrdering::SeqCst)
+    }
+}
+
+impl<C: Clock> Clock for Arc<C> {
+    fn now_tick(&self) -> i64 {
+        (**self).now_tick()
+    }
 }
 
 /// Parsed WID components.
-#[derive(Debug, Clone, PartialEq)]
+///
+/// Ordered by generation: timestamp, then sequence, then padding
+/// (lexicographic, absent first) so IDs colliding on tick and sequence across
+/// generators still have a deterministic order; `raw` breaks any remaining tie.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct ParsedWid {
     pub raw: String,
     pub timestamp: DateTime<Utc>,
-    pub sequence: u32,
+    pub sequence: u64,
     pub padding: Option<String>,
 }
 
@@ -78,6 +290,164 @@ impl ParsedWid {
     pub fn timestamp_sec(&self) -> i64 {
         self.timestamp.timestamp()
     }
+
+    /// Wall-clock age of the ID; negative when its timestamp is in the future
+    /// (e.g. clock skew between generator and reader).
+    pub fn elapsed(&self) -> chrono::Duration {
+        Utc::now() - self.timestamp
+    }
+
+    /// `elapsed` in whole seconds, truncated toward zero.
+    pub fn age_secs(&self) -> i64 {
+        self.elapsed().num_seconds()
+    }
+
+    /// Whether the ID is more than `secs` seconds old. Future-dated IDs never
+    /// are.
+    pub fn is_older_than(&self, secs: u64) -> bool {
+        is_older_than(self.elapsed(), secs)
+    }
+}
+
+pub(crate) fn is_older_than(elapsed: chrono::Duration, secs: u64) -> bool {
+    // Thresholds beyond chrono's range exceed any representable age.
+    i64::try_from(secs)
+        .ok()
+        .and_then(chrono::Duration::try_seconds)
+        .is_some_and(|limit| elapsed > limit)
+}
+
+impl Ord for ParsedWid {
+    fn cmp(&self, other: &Self) -> std::cmp::Ordering {
+        (self.timestamp, self.sequence, &self.padding, &self.raw).cmp(&(
+            other.timestamp,
+            other.sequence,
+            &other.padding,
+            &other.raw,
+        ))
+    }
+}
+
+impl PartialOrd for ParsedWid {
+    fn partial_cmp(&self, other: &Self) -> Option<std::cmp::Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+/// Sort `ids` into generation order, parsing each ID once.
+///
+/// Parseable IDs come first, ordered as `ParsedWid`; IDs that do not parse
+/// under `w`/`z`/`time_unit` follow in raw string order.
+pub fn stable_sort(ids: &mut Vec<String>, w: usize, z: usize, time_unit: TimeUnit) {
+    // `Ok` sorts before `Err`, which puts unparseable IDs last.
+    let mut entries: Vec<Result<ParsedWid, String>> = std::mem::take(ids)
+        .into_iter()
+        .map(|id| parse_wid_with_unit(&id, w, z, time_unit).map_err(|_| id))
+        .collect();
+    entries.sort();
+    *ids = entries
+        .into_iter()
+        .map(|entry| entry.map_or_else(|raw| raw, |parsed| parsed.raw))
+        .collect();
+}
+
+/// Replace `ids` with freshly minted, contiguous WIDs that keep their order,
+/// e.g. for anonymized exports.
+///
+/// Distinct inputs are ordered as by `stable_sort` and assigned consecutive
+/// `(tick, seq)` slots from `(base_tick, 0)`, rolling into the next tick
+/// after `10^w - 1`, with new random padding. Returns the new IDs in order
+/// and the old-to-new map. Inputs that do not parse with `w`/`z`/`time_unit`
+/// are skipped: they get no slot and no map entry, so callers can detect them
+/// as missing keys. Invalid `w`/`z` yield empty results.
+pub fn renumber(
+    ids: &[&str],
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    base_tick: i64,
+) -> (Vec<String>, HashMap<String, String>) {
+    let (mut parsed, _) = parse_batch(ids, w, z, time_unit);
+    parsed.sort();
+    parsed.dedup_by(|a, b| a.raw == b.raw);
+    let sorted: Vec<String> = parsed.into_iter().map(|p| p.raw).collect();
+
+    let token = Reservation {
+        tick: base_tick,
+        start_seq: 0,
+        count: sorted.len(),
+        descending: false,
+        version_tag: None,
+        shard: None,
+    };
+    let Ok(fresh) = token.try_iter(w, z, time_unit) else {
+        return (Vec::new(), HashMap::new());
+    };
+    let fresh: Vec<String> = fresh.collect();
+    let mapping = sorted.into_iter().zip(fresh.iter().cloned()).collect();
+    (fresh, mapping)
+}
+
+/// Raw capture groups of a WID from `capture_fields`, uninterpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WidFields<'a> {
+    /// `YYYYMMDD`.
+    pub date: &'a str,
+    /// `HHMMSS` plus the sub-second digits of the time unit.
+    pub time: &'a str,
+    pub sequence: &'a str,
+    pub padding: Option<&'a str>,
+}
+
+/// Borrowed WID components from `parse_wid_ref`.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ParsedWidRef<'a> {
+    pub raw: &'a str,
+    pub timestamp: DateTime<Utc>,
+    pub sequence: u64,
+    pub padding: Option<&'a str>,
+}
+
+impl ParsedWidRef<'_> {
+    /// Copy the borrowed fields into an owned `ParsedWid`.
+    pub fn into_owned(self) -> ParsedWid {
+        ParsedWid {
+            raw: self.raw.to_string(),
+            timestamp: self.timestamp,
+            sequence: self.sequence,
+            padding: self.padding.map(str::to_string),
+        }
+    }
+}
+
+/// Parsed WIDs laid out as parallel columns for Arrow/Parquet-style export.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct WidColumns {
+    /// Unix epoch milliseconds, truncated: exact for `sec` and `ms` WIDs, while
+    /// `ns` WIDs keep their sub-millisecond part in `sub_ms_nanos`.
+    pub timestamps: Vec<i64>,
+    /// Nanoseconds past `timestamps[i]`, in `0..1_000_000`; always 0 for `sec`
+    /// and `ms` WIDs.
+    pub sub_ms_nanos: Vec<u32>,
+    pub sequences: Vec<u64>,
+    pub paddings: Vec<Option<String>>,
+}
+
+/// Convert parsed WIDs into `WidColumns`; row `i` of every column comes from
+/// `parsed[i]`.
+pub fn to_columns(parsed: &[ParsedWid]) -> WidColumns {
+    WidColumns {
+        timestamps: parsed
+            .iter()
+            .map(|p| p.timestamp.timestamp_millis())
+            .collect(),
+        sub_ms_nanos: parsed
+            .iter()
+            .map(|p| p.timestamp.timestamp_subsec_nanos() % 1_000_000)
+            .collect(),
+        sequences: parsed.iter().map(|p| p.sequence).collect(),
+        paddings: parsed.iter().map(|p| p.padding.clone()).collect(),
+    }
 }
 
 static WID_PATTERN_W4_Z6_SEC: Lazy<Regex> =
@@ -85,10 +455,7 @@ static WID_PATTERN_W4_Z6_SEC: Lazy<Regex> =
 
 fn build_pattern(w: usize, z: usize, time_unit: TimeUnit) -> Regex {
     let seq_part = format!(r"(\d{{{w}}})");
-    let time_digits = match time_unit {
-        TimeUnit::Sec => 6,
-        TimeUnit::Ms => 9,
-    };
+    let time_digits = time_unit.time_digits();
     let pad_part = if z > 0 {
         format!(r"(?:-([0-9a-f]{{{z}}}))?$")
     } else {
@@ -99,29 +466,52 @@ fn build_pattern(w: usize, z: usize, time_unit: TimeUnit) -> Regex {
     Regex::new(&pattern).unwrap()
 }
 
-fn parse_timestamp(time_unit: TimeUnit, date_str: &str, time_str: &str) -> Option<DateTime<Utc>> {
+pub(crate) fn parse_timestamp(
+    time_unit: TimeUnit,
+    date_str: &str,
+    time_str: &str,
+) -> Option<DateTime<Utc>> {
     let year: i32 = date_str[0..4].parse().ok()?;
     let month: u32 = date_str[4..6].parse().ok()?;
     let day: u32 = date_str[6..8].parse().ok()?;
+    let hour: u32 = time_str[0..2].parse().ok()?;
+    let minute: u32 = time_str[2..4].parse().ok()?;
+    let second: u32 = time_str[4..6].parse().ok()?;
+    let ts = Utc
+        .with_ymd_and_hms(year, month, day, hour, minute, second)
+        .single()?;
 
     match time_unit {
-        TimeUnit::Sec => {
-            let hour: u32 = time_str[0..2].parse().ok()?;
-            let minute: u32 = time_str[2..4].parse().ok()?;
-            let second: u32 = time_str[4..6].parse().ok()?;
-            Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
-                .single()
-        }
+        TimeUnit::Sec => Some(ts),
         TimeUnit::Ms => {
-            let hour: u32 = time_str[0..2].parse().ok()?;
-            let minute: u32 = time_str[2..4].parse().ok()?;
-            let second: u32 = time_str[4..6].parse().ok()?;
             let millis: u32 = time_str[6..9].parse().ok()?;
-            Utc.with_ymd_and_hms(year, month, day, hour, minute, second)
-                .single()?
-                .with_nanosecond(millis * 1_000_000)
+            ts.with_nanosecond(millis * 1_000_000)
+        }
+        TimeUnit::Ns => {
+            let nanos: u32 = time_str[6..15].parse().ok()?;
+            let ts = ts.with_nanosecond(nanos)?;
+            // Reject instants past `MAX_TICK_NS` so every parsed `ns` WID has a tick.
+            ts.timestamp_nanos_opt().map(|_| ts)
+        }
+    }
+}
+
+/// Check the fixed-width date and time fields of `<date>T<time>.` up front,
+/// so a dropped or extra digit is reported precisely. Inputs without that
+/// shape are left for the regex to reject.
+pub(crate) fn check_timestamp_lengths(id: &str, time_unit: TimeUnit) -> Result<(), WidError> {
+    let Some((date, time)) = id.split_once('.').and_then(|(ts, _)| ts.split_once('T')) else {
+        return Ok(());
+    };
+    for (field, expected) in [(date, 8), (time, time_unit.time_digits())] {
+        if field.len() != expected {
+            return Err(WidError::InvalidTimestampLength {
+                expected,
+                found: field.len(),
+            });
         }
     }
+    Ok(())
 }
 
 /// Validate a WID string for a specific time unit.
@@ -129,6 +519,28 @@ pub fn validate_wid_with_unit(wid: &str, w: usize, z: usize, time_unit: TimeUnit
     parse_wid_with_unit(wid, w, z, time_unit).is_ok()
 }
 
+/// Validate a WID and require its timestamp to be at most `max_age` old and at
+/// most `max_future` ahead of the current wall clock.
+pub fn validate_wid_in_window(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    max_age: Duration,
+    max_future: Duration,
+) -> bool {
+    parse_wid_ref(wid, w, z, time_unit)
+        .is_ok_and(|parsed| in_window(parsed.timestamp, max_age, max_future))
+}
+
+/// `now - ts <= max_age && ts - now <= max_future`; bounds past chrono's range
+/// never reject.
+pub(crate) fn in_window(ts: DateTime<Utc>, max_age: Duration, max_future: Duration) -> bool {
+    let bound = |d: Duration| chrono::Duration::from_std(d).unwrap_or(chrono::Duration::MAX);
+    let age = Utc::now() - ts;
+    age <= bound(max_age) && -age <= bound(max_future)
+}
+
 /// Validate a WID string in `sec` mode.
 pub fn validate_wid(wid: &str, w: usize, z: usize) -> bool {
     validate_wid_with_unit(wid, w, z, TimeUnit::Sec)
@@ -141,140 +553,1015 @@ pub fn parse_wid_with_unit(
     z: usize,
     time_unit: TimeUnit,
 ) -> Result<ParsedWid, WidError> {
+    parse_wid_ref(wid, w, z, time_unit).map(ParsedWidRef::into_owned)
+}
+
+/// Parse a WID without allocating: every string field borrows from `wid`.
+pub fn parse_wid_ref(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<ParsedWidRef<'_>, WidError> {
+    if wid.trim().is_empty() {
+        return Err(WidError::Empty);
+    }
+    check_parse_params(w, z)?;
+
+    if w == 4 && z == 6 && time_unit == TimeUnit::Sec {
+        parse_with_pattern(wid, z, time_unit, &WID_PATTERN_W4_Z6_SEC)
+    } else {
+        parse_with_pattern(wid, z, time_unit, &build_pattern(w, z, time_unit))
+    }
+}
+
+/// Parse an ID minted by `WidGen::new_with_format`, using `parser` to split
+/// it into fields. The fields are reassembled in the canonical layout and
+/// validated as by `parse_wid_with_unit`, so errors describe that canonical
+/// form; `raw` is the ID as given.
+pub fn parse_wid_with_format(
+    wid: &str,
+    parser: &dyn WidFormatParser,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<ParsedWid, WidError> {
+    let (ts, seq, pad) = parser
+        .split(wid)
+        .ok_or_else(|| WidError::InvalidFormat(wid.to_string()))?;
+    let canonical = CanonicalFormat.format(ts, seq, pad);
+    let mut parsed = parse_wid_with_unit(&canonical, w, z, time_unit)?;
+    parsed.raw = wid.to_string();
+    Ok(parsed)
+}
+
+/// Parse every ID in `ids` with one compiled pattern, splitting the results
+/// into successes (in input order) and `(index, error)` failures instead of
+/// stopping at the first bad ID. Invalid `w`/`z` fail every entry.
+pub fn parse_batch(
+    ids: &[&str],
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> (Vec<ParsedWid>, Vec<(usize, WidError)>) {
+    if let Err(e) = check_parse_params(w, z) {
+        // `WidError` is not `Clone`, but both parameter errors are unit variants.
+        let invalid_w = matches!(e, WidError::InvalidW);
+        let failures = (0..ids.len())
+            .map(|i| {
+                let e = if invalid_w {
+                    WidError::InvalidW
+                } else {
+                    WidError::InvalidZ
+                };
+                (i, e)
+            })
+            .collect();
+        return (Vec::new(), failures);
+    }
+    let pattern = build_pattern(w, z, time_unit);
+
+    let mut parsed = Vec::with_capacity(ids.len());
+    let mut failures = Vec::new();
+    for (i, id) in ids.iter().enumerate() {
+        let result = if id.trim().is_empty() {
+            Err(WidError::Empty)
+        } else {
+            parse_with_pattern(id, z, time_unit, &pattern)
+        };
+        match result {
+            Ok(wid) => parsed.push(wid.into_owned()),
+            Err(e) => failures.push((i, e)),
+        }
+    }
+    (parsed, failures)
+}
+
+fn check_parse_params(w: usize, z: usize) -> Result<(), WidError> {
     if w == 0 || w > MAX_W {
         return Err(WidError::InvalidW);
     }
     if z > MAX_Z {
         return Err(WidError::InvalidZ);
     }
+    Ok(())
+}
 
-    let pattern = if w == 4 && z == 6 && time_unit == TimeUnit::Sec {
-        &*WID_PATTERN_W4_Z6_SEC
-    } else {
-        &build_pattern(w, z, time_unit)
-    };
-
-    let caps = pattern
-        .captures(wid)
-        .ok_or_else(|| WidError::InvalidFormat(wid.to_string()))?;
+/// Body of `parse_wid_ref` once the parameters are checked and `pattern` is
+/// `build_pattern(w, z, time_unit)`.
+fn parse_with_pattern<'a>(
+    wid: &'a str,
+    z: usize,
+    time_unit: TimeUnit,
+    pattern: &Regex,
+) -> Result<ParsedWidRef<'a>, WidError> {
+    check_timestamp_lengths(wid, time_unit)?;
 
-    let date_str = &caps[1];
-    let time_str = &caps[2];
-    let seq_str = &caps[3];
-    let padding = if z > 0 {
-        caps.get(4).map(|m| m.as_str().to_string())
-    } else {
-        None
-    };
+    let fields =
+        captures_with(wid, z, pattern).ok_or_else(|| WidError::InvalidFormat(wid.to_string()))?;
 
     let timestamp =
-        parse_timestamp(time_unit, date_str, time_str).ok_or(WidError::InvalidTimestamp)?;
+        parse_timestamp(time_unit, fields.date, fields.time).ok_or(WidError::InvalidTimestamp)?;
 
-    let sequence: u32 = seq_str
+    let sequence: u64 = fields
+        .sequence
         .parse()
         .map_err(|_| WidError::InvalidFormat(wid.to_string()))?;
 
-    Ok(ParsedWid {
-        raw: wid.to_string(),
+    Ok(ParsedWidRef {
+        raw: wid,
         timestamp,
         sequence,
-        padding,
+        padding: fields.padding,
     })
 }
 
-/// Parse a WID string into its components in `sec` mode.
-pub fn parse_wid(wid: &str, w: usize, z: usize) -> Result<ParsedWid, WidError> {
-    parse_wid_with_unit(wid, w, z, TimeUnit::Sec)
+fn captures_with<'a>(wid: &'a str, z: usize, pattern: &Regex) -> Option<WidFields<'a>> {
+    let caps = pattern.captures(wid)?;
+    // `&caps[i]` would borrow from `caps`; `get` keeps the `'a` of `wid`.
+    // Groups 1-3 are mandatory, so a match always has them.
+    let field = |i: usize| caps.get(i).expect("mandatory WID group").as_str();
+    Some(WidFields {
+        date: field(1),
+        time: field(2),
+        sequence: field(3),
+        padding: if z > 0 {
+            caps.get(4).map(|m| m.as_str())
+        } else {
+            None
+        },
+    })
 }
 
-/// WID generator with monotonic sequence and collision-resistant padding.
-pub struct WidGen {
+/// Match `wid` against the WID grammar for `w`/`z`/`time_unit` and return
+/// the raw capture groups, for callers that interpret the fields
+/// themselves. Only the shape is checked: the date and time are not
+/// validated as a real instant. `None` when the grammar does not match or
+/// `w`/`z` are out of range.
+pub fn capture_fields(wid: &str, w: usize, z: usize, time_unit: TimeUnit) -> Option<WidFields<'_>> {
+    check_parse_params(w, z).ok()?;
+    if w == 4 && z == 6 && time_unit == TimeUnit::Sec {
+        captures_with(wid, z, &WID_PATTERN_W4_Z6_SEC)
+    } else {
+        captures_with(wid, z, &build_pattern(w, z, time_unit))
+    }
+}
+
+/// Whether `wid`'s timestamp is strictly earlier than `cutover`, e.g. to
+/// route IDs minted before a format migration.
+pub fn before(
+    wid: &str,
+    cutover: DateTime<Utc>,
     w: usize,
     z: usize,
     time_unit: TimeUnit,
-    max_seq: i64,
-    last_tick: i64,
-    last_seq: i64,
-    cached_tick: i64,
-    cached_ts: String,
+) -> Result<bool, WidError> {
+    Ok(parse_wid_ref(wid, w, z, time_unit)?.timestamp < cutover)
 }
 
-impl WidGen {
-    /// Create a new WID generator in `sec` mode.
-    pub fn new(w: usize, z: usize, scope: Option<String>) -> Result<Self, WidError> {
-        Self::new_with_time_unit(w, z, scope, TimeUnit::Sec)
+/// Earliest year `parse_wid_bounded` accepts.
+pub const MIN_BOUNDED_YEAR: i32 = 2000;
+
+/// Parse a WID, rejecting timestamps outside `MIN_BOUNDED_YEAR..=max_year`.
+///
+/// A sanity gate for untrusted input; `parse_wid_with_unit` stays unbounded.
+pub fn parse_wid_bounded(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    max_year: i32,
+) -> Result<ParsedWid, WidError> {
+    let parsed = parse_wid_with_unit(wid, w, z, time_unit)?;
+    let year = parsed.timestamp.year();
+    if !(MIN_BOUNDED_YEAR..=max_year).contains(&year) {
+        return Err(WidError::TimestampOutOfRange {
+            year,
+            min: MIN_BOUNDED_YEAR,
+            max: max_year,
+        });
     }
+    Ok(parsed)
+}
 
-    /// Create a new WID generator with a chosen time unit.
-    pub fn new_with_time_unit(
-        w: usize,
-        z: usize,
-        scope: Option<String>,
-        time_unit: TimeUnit,
-    ) -> Result<Self, WidError> {
-        // W > MAX_W would overflow the i64 sequence below (10^19 > i64::MAX).
-        if w == 0 || w > MAX_W {
-            return Err(WidError::InvalidW);
-        }
-        if z > MAX_Z {
-            return Err(WidError::InvalidZ);
-        }
+/// Parse a WID minted by `WidGen::new_descending`, recovering the true
+/// timestamp and sequence.
+pub fn parse_wid_descending(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<ParsedWid, WidError> {
+    let mut parsed = parse_wid_with_unit(wid, w, z, time_unit)?;
+    let encoded_tick = time_unit
+        .tick_of(parsed.timestamp)
+        .ok_or(WidError::InvalidTimestamp)?;
+    let max_seq = 10_i64.pow(w as u32) - 1;
+    let (tick, seq) = invert_slot(time_unit, max_seq, encoded_tick, parsed.sequence as i64);
+    parsed.timestamp = time_unit
+        .datetime_of(tick)
+        .ok_or(WidError::InvalidTimestamp)?;
+    parsed.sequence = u64::try_from(seq).map_err(|_| WidError::InvalidFormat(wid.to_string()))?;
+    Ok(parsed)
+}
 
-        if scope.is_some() {
-            return Err(WidError::InvalidScope);
-        }
+/// Parse a WID string into its components in `sec` mode.
+pub fn parse_wid(wid: &str, w: usize, z: usize) -> Result<ParsedWid, WidError> {
+    parse_wid_with_unit(wid, w, z, TimeUnit::Sec)
+}
 
-        let max_seq = 10_i64.pow(w as u32) - 1;
+pub(crate) fn format_tick(time_unit: TimeUnit, tick: i64) -> String {
+    format_datetime(time_unit, time_unit.datetime_of(tick).unwrap())
+}
 
-        Ok(Self {
-            w,
-            z,
-            time_unit,
-            max_seq,
-            last_tick: 0,
-            last_seq: -1,
-            cached_tick: -1,
-            cached_ts: String::new(),
-        })
-    }
+/// WID timestamp of `ts`, truncated to `time_unit`.
+fn format_datetime(time_unit: TimeUnit, ts: DateTime<Utc>) -> String {
+    let fmt = match time_unit {
+        TimeUnit::Sec => "%Y%m%dT%H%M%S",
+        TimeUnit::Ms => "%Y%m%dT%H%M%S%3f",
+        TimeUnit::Ns => "%Y%m%dT%H%M%S%9f",
+    };
+    ts.format(fmt).to_string()
+}
 
-    /// Create a generator with default parameters (W=4, Z=6, `sec`).
-    pub fn default_params() -> Self {
-        Self::new(4, 6, None).expect("default parameters should always be valid")
+/// Map a `(tick, seq)` slot to its descending-mode encoding. The mapping is
+/// its own inverse, so it also decodes.
+fn invert_slot(time_unit: TimeUnit, max_seq: i64, tick: i64, seq: i64) -> (i64, i64) {
+    (time_unit.max_tick() - tick, max_seq - seq)
+}
+
+/// Overwrite the first padding character with the hex digit `tag`.
+fn apply_version_tag(wid: &mut String, z: usize, tag: u8) {
+    let start = wid.len() - z;
+    wid.replace_range(start..=start, &format!("{tag:x}"));
+}
+
+/// Read the version tag written by `WidGen::new_with_version_tag`: the first
+/// padding character, as a hex digit.
+pub fn extract_version_tag(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<u8, WidError> {
+    let parsed = parse_wid_ref(wid, w, z, time_unit)?;
+    parsed
+        .padding
+        .and_then(|pad| pad.chars().next())
+        .and_then(|c| c.to_digit(16))
+        .map(|tag| tag as u8)
+        .ok_or_else(|| WidError::InvalidFormat(wid.to_string()))
+}
+
+fn format_wid(ts: &str, seq: i64, w: usize, z: usize, padding: &mut dyn PaddingProvider) -> String {
+    let mut wid = format!("{ts}.{seq:0w$}Z");
+    if z > 0 {
+        wid.push('-');
+        padding.fill(&mut wid, z, HEX_ALPHABET);
     }
+    wid
+}
 
-    fn ts_for_tick(&mut self, tick: i64) -> &str {
-        if tick != self.cached_tick {
-            self.cached_tick = tick;
-            self.cached_ts = match self.time_unit {
-                TimeUnit::Sec => {
-                    let dt = Utc.timestamp_opt(tick, 0).unwrap();
-                    dt.format("%Y%m%dT%H%M%S").to_string()
-                }
-                TimeUnit::Ms => {
-                    let sec = tick / 1000;
-                    let ms = (tick % 1000) as u32;
-                    let dt = Utc.timestamp_opt(sec, ms * 1_000_000).unwrap();
-                    dt.format("%Y%m%dT%H%M%S%3f").to_string()
-                }
-            };
+/// How much of a corpus validates under a candidate config.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct CoverageReport {
+    pub matched: usize,
+    pub unmatched: Vec<String>,
+}
+
+/// Check existing IDs against a proposed `w`/`z`/`time_unit` before migrating.
+pub fn config_covers(ids: &[&str], w: usize, z: usize, time_unit: TimeUnit) -> CoverageReport {
+    let mut report = CoverageReport::default();
+    for id in ids {
+        if validate_wid_with_unit(id, w, z, time_unit) {
+            report.matched += 1;
+        } else {
+            report.unmatched.push(id.to_string());
         }
-        &self.cached_ts
     }
+    report
+}
 
-    fn current_tick(time_unit: TimeUnit) -> i64 {
-        let dur = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
-        match time_unit {
-            TimeUnit::Sec => dur.as_secs() as i64,
-            TimeUnit::Ms => dur.as_millis() as i64,
-        }
+/// Count IDs by age relative to `now`, e.g. for retention reports.
+///
+/// `edges` are ascending upper bounds: bucket `i` holds IDs younger than
+/// `edges[i]` but not younger than `edges[i - 1]`, and a final overflow bucket
+/// holds everything at least `edges[last]` old, so the result has
+/// `edges.len() + 1` entries. IDs dated after `now` count as age zero;
+/// unparseable IDs are skipped.
+pub fn age_buckets(
+    ids: &[&str],
+    now: DateTime<Utc>,
+    edges: &[Duration],
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Vec<usize> {
+    let mut counts = vec![0; edges.len() + 1];
+    for parsed in parse_batch(ids, w, z, time_unit).0 {
+        let age = (now - parsed.timestamp).to_std().unwrap_or(Duration::ZERO);
+        counts[edges.partition_point(|edge| age >= *edge)] += 1;
     }
+    counts
+}
 
-    /// Generate the next WID (domain API).
-    pub fn next_wid(&mut self) -> String {
-        let now_tick = Self::current_tick(self.time_unit);
-        let mut tick = if now_tick > self.last_tick {
-            now_tick
+/// Whether `b` could have been minted after `a` by one monotonic generator.
+///
+/// Both IDs must parse under the given config. `b` is plausible when it is in
+/// a later tick, or in the same tick with a higher sequence; a repeated slot
+/// is never plausible since a generator emits each `(tick, seq)` once.
+pub fn same_generator_plausible(
+    a: &str,
+    b: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<bool, WidError> {
+    let a = parse_wid_with_unit(a, w, z, time_unit)?;
+    let b = parse_wid_with_unit(b, w, z, time_unit)?;
+    Ok((b.timestamp, b.sequence) > (a.timestamp, a.sequence))
+}
+
+/// Smallest W whose sequence space covers `ids_per_sec` without borrowing ticks.
+///
+/// In `ms` and `ns` mode the rate is spread over the ticks of a second
+/// (rounded up).
+/// The result is at least 1 and exceeds `MAX_W` only for rates no generator
+/// can sustain.
+pub fn min_w_for_rate(ids_per_sec: u64, time_unit: TimeUnit) -> usize {
+    let ids_per_tick = ids_per_sec.div_ceil(time_unit.ticks_per_sec() as u64);
+    let mut w = 1;
+    let mut slots: u128 = 10;
+    while slots < u128::from(ids_per_tick) {
+        w += 1;
+        slots *= 10;
+    }
+    w
+}
+
+/// `k` bloom-filter hash values for a WID, via double hashing.
+///
+/// Two 64-bit halves `h1`/`h2` of the SHA-256 of the raw string seed
+/// `h1 + i * h2` (Kirsch–Mitzenmacher); `h2` is forced odd so the sequence
+/// never collapses to a single value. The input is hashed as-is, so any
+/// string works and no parsing is done.
+pub fn hash_for_bloom(wid: &str, k: usize) -> Vec<u64> {
+    let digest = Sha256::digest(wid.as_bytes());
+    let h1 = u64::from_be_bytes(digest[..8].try_into().expect("8-byte slice"));
+    let h2 = u64::from_be_bytes(digest[8..16].try_into().expect("8-byte slice")) | 1;
+    (0..k as u64)
+        .map(|i| h1.wrapping_add(i.wrapping_mul(h2)))
+        .collect()
+}
+
+/// Re-encode a WID from one time unit to another, keeping its sequence and
+/// padding.
+///
+/// Returns the converted WID and whether the conversion was lossy: converting
+/// to a coarser unit (e.g. `ms` to `sec`) drops the finer digits, so it is
+/// lossy whenever they are non-zero. Converting to a finer unit never is, but
+/// fails with `InvalidTimestamp` past `MAX_TICK_NS` when the target is `ns`.
+pub fn convert_time_unit(
+    wid: &str,
+    w: usize,
+    z: usize,
+    from: TimeUnit,
+    to: TimeUnit,
+) -> Result<(String, bool), WidError> {
+    let parsed = parse_wid_ref(wid, w, z, from)?;
+    let tick = to
+        .tick_of(parsed.timestamp)
+        .ok_or(WidError::InvalidTimestamp)?;
+    let nanos_per_tick = (1_000_000_000 / to.ticks_per_sec()) as u32;
+    let lossy = !parsed
+        .timestamp
+        .timestamp_subsec_nanos()
+        .is_multiple_of(nanos_per_tick);
+
+    let mut out = format!("{}.{:0w$}Z", format_tick(to, tick), parsed.sequence);
+    if let Some(padding) = parsed.padding {
+        out.push('-');
+        out.push_str(padding);
+    }
+    Ok((out, lossy))
+}
+
+/// Inclusive lexicographic bounds of every WID minted on `date` (UTC).
+///
+/// `lo` is midnight with sequence and padding all zeros; `hi` is the last
+/// second (or millisecond) of the day with the maximum sequence and `f`
+/// padding, so `id >= lo && id <= hi` selects exactly that day.
+pub fn day_bounds(date: NaiveDate, w: usize, z: usize, time_unit: TimeUnit) -> (String, String) {
+    let day = date.format("%Y%m%d");
+    let digits = time_unit.time_digits();
+    let bound = |time: &str, digit: char, hex: char| {
+        bound_wid(&format!("{day}T{time:.digits$}"), w, z, digit, hex)
+    };
+    (
+        bound("000000000000000", '0', '0'),
+        bound("235959999999999", '9', 'f'),
+    )
+}
+
+/// Smallest WID that can be minted at `ts` (truncated to `time_unit`):
+/// sequence 0 and all-`0` padding. An inclusive lower bound for range scans.
+pub fn min_wid_at(ts: DateTime<Utc>, w: usize, z: usize, time_unit: TimeUnit) -> String {
+    bound_wid(&format_datetime(time_unit, ts), w, z, '0', '0')
+}
+
+/// Largest WID that can be minted at `ts` (truncated to `time_unit`):
+/// maximum sequence and all-`f` padding. An inclusive upper bound for range
+/// scans.
+pub fn max_wid_at(ts: DateTime<Utc>, w: usize, z: usize, time_unit: TimeUnit) -> String {
+    bound_wid(&format_datetime(time_unit, ts), w, z, '9', 'f')
+}
+
+/// `timestamp` with every sequence digit set to `digit` and every padding
+/// character to `hex`.
+fn bound_wid(timestamp: &str, w: usize, z: usize, digit: char, hex: char) -> String {
+    let mut id = format!("{timestamp}.{}Z", digit.to_string().repeat(w));
+    if z > 0 {
+        id.push('-');
+        id.push_str(&hex.to_string().repeat(z));
+    }
+    id
+}
+
+/// Timestamp precision kept by `coarsen`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Granularity {
+    Second,
+    Minute,
+    Hour,
+    Day,
+}
+
+/// Reduce a WID's timing precision, e.g. for privacy.
+///
+/// Time fields below `granularity` (including any `ms`/`ns` fraction) and the
+/// sequence are zeroed; padding is kept. The result is still a valid WID for
+/// the same config, but the conversion is lossy and not unique: every ID
+/// minted in the same minute (say) coarsens to the same prefix.
+pub fn coarsen(
+    wid: &str,
+    granularity: Granularity,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<String, WidError> {
+    let parsed = parse_wid_ref(wid, w, z, time_unit)?;
+    let ts = parsed.timestamp;
+    let (hour, minute, second) = match granularity {
+        Granularity::Second => (ts.hour(), ts.minute(), ts.second()),
+        Granularity::Minute => (ts.hour(), ts.minute(), 0),
+        Granularity::Hour => (ts.hour(), 0, 0),
+        Granularity::Day => (0, 0, 0),
+    };
+    let coarse = ts
+        .date_naive()
+        .and_hms_opt(hour, minute, second)
+        .ok_or(WidError::InvalidTimestamp)?
+        .and_utc();
+    let tick = time_unit
+        .tick_of(coarse)
+        .ok_or(WidError::InvalidTimestamp)?;
+
+    let mut out = format!("{}.{}Z", format_tick(time_unit, tick), "0".repeat(w));
+    if let Some(padding) = parsed.padding {
+        out.push('-');
+        out.push_str(padding);
+    }
+    Ok(out)
+}
+
+/// Drop the `-<pad>` suffix, leaving the `Z=0` form of `wid`.
+///
+/// `wid` must validate under `w`, `z` and `time_unit`; with `z == 0` it is
+/// returned unchanged. The result validates with the same `w` and unit and
+/// `Z=0`, and keeps the original timestamp and sequence, so it sorts the same
+/// relative to other stripped IDs (ties only where padding differed).
+pub fn strip_padding(
+    wid: &str,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> Result<String, WidError> {
+    parse_wid_ref(wid, w, z, time_unit)?;
+    Ok(wid
+        .split_once('-')
+        .map_or(wid, |(head, _)| head)
+        .to_string())
+}
+
+/// The first WID of the whole second after `reference`.
+///
+/// The result has sequence 0 and all-zero padding, so it sorts before any real
+/// WID minted in that second. In `ms` and `ns` mode the fractional digits are
+/// all zero.
+pub fn next_second_boundary(
+    reference: &ParsedWid,
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+) -> String {
+    let sec = reference.timestamp.timestamp() + 1;
+    // Saturates only for an `ns` reference in the last second before `MAX_TICK_NS`.
+    let tick = sec.saturating_mul(time_unit.ticks_per_sec());
+    let mut wid = format!("{}.{}Z", format_tick(time_unit, tick), "0".repeat(w));
+    if z > 0 {
+        wid.push('-');
+        wid.push_str(&"0".repeat(z));
+    }
+    wid
+}
+
+/// A block of consecutive sequence slots handed out by `WidGen::reserve_token`.
+///
+/// The token is plain data, so it can be shipped to a worker that expands it
+/// into concrete WIDs with `iter` without contacting the allocator again.
+/// Slots past the generator's `max_seq` roll over into the following tick,
+/// exactly as `next_wid` would.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Reservation {
+    pub tick: i64,
+    pub start_seq: i64,
+    pub count: usize,
+    /// Set when minted by a `WidGen::new_descending` generator.
+    #[serde(default)]
+    pub descending: bool,
+    /// Set when minted by a `WidGen::new_with_version_tag` generator.
+    #[serde(default)]
+    pub version_tag: Option<u8>,
+    /// `(shard, shard_digits)` when minted by a `WidGen::new_sharded` generator.
+    #[serde(default)]
+    pub shard: Option<(u32, usize)>,
+}
+
+impl Reservation {
+    /// Expand the reservation into WIDs.
+    ///
+    /// `w`, `z` and `time_unit` must match the generator that minted the token.
+    /// Padding comes from the thread-local RNG; see `try_iter_with_padding` or
+    /// `WidGen::expand_reservation` to use a custom provider.
+    ///
+    /// # Panics
+    ///
+    /// Panics where `try_iter` fails: on invalid `w`/`z`, a shard that does not
+    /// fit `w`, or a block running past the last representable slot.
+    pub fn iter(&self, w: usize, z: usize, time_unit: TimeUnit) -> impl Iterator<Item = String> {
+        self.try_iter(w, z, time_unit)
+            .expect("reservation does not match w/z/time_unit")
+    }
+
+    /// Fallible counterpart of `iter`, e.g. for tokens received from peers.
+    pub fn try_iter(
+        &self,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+    ) -> Result<impl Iterator<Item = String> + '_, WidError> {
+        self.expand(w, z, time_unit, HexPadding)
+    }
+
+    /// Like `try_iter`, drawing padding from `padding`.
+    pub fn try_iter_with_padding<'a>(
+        &'a self,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        padding: &'a mut dyn PaddingProvider,
+    ) -> Result<impl Iterator<Item = String> + 'a, WidError> {
+        self.expand(w, z, time_unit, padding)
+    }
+
+    fn expand<P: PaddingProvider>(
+        &self,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        mut padding: P,
+    ) -> Result<impl Iterator<Item = String>, WidError> {
+        if w == 0 || w > MAX_W {
+            return Err(WidError::InvalidW);
+        }
+        if z > MAX_Z {
+            return Err(WidError::InvalidZ);
+        }
+        let (shard, shard_digits) = self.shard.unwrap_or((0, 0));
+        if shard_digits >= w {
+            return Err(WidError::InvalidShard {
+                shard,
+                shard_digits,
+            });
+        }
+        let slots_per_tick = 10_i64.pow((w - shard_digits) as u32);
+        let shard_base = i64::from(shard) * slots_per_tick;
+        let count = i64::try_from(self.count)
+            .ok()
+            .filter(|_| {
+                block_end(self.tick, self.start_seq, self.count, slots_per_tick)
+                    .is_some_and(|(tick, _)| tick <= time_unit.max_tick())
+            })
+            .ok_or(WidError::ReservationOutOfRange(self.count))?;
+        Ok((0..count).map(move |i| {
+            let slot = self.start_seq + i;
+            let mut tick = self.tick + slot / slots_per_tick;
+            let mut seq = slot % slots_per_tick;
+            if self.descending {
+                (tick, seq) = invert_slot(time_unit, slots_per_tick - 1, tick, seq);
+            }
+            seq += shard_base;
+            let mut wid = format_wid(&format_tick(time_unit, tick), seq, w, z, &mut padding);
+            if let Some(tag) = self.version_tag {
+                apply_version_tag(&mut wid, z, tag);
+            }
+            wid
+        }))
+    }
+}
+
+/// Last `(tick, seq)` of a block of `count` slots starting at
+/// `(tick, start_seq)`, or `None` if it overflows `i64`. An empty block ends
+/// where it starts.
+fn block_end(tick: i64, start_seq: i64, count: usize, slots_per_tick: i64) -> Option<(i64, i64)> {
+    let last = start_seq.checked_add(i64::try_from(count).ok()?.saturating_sub(1).max(0))?;
+    Some((
+        tick.checked_add(last / slots_per_tick)?,
+        last % slots_per_tick,
+    ))
+}
+
+/// Field-by-field result of `parse_wid_partial`.
+#[derive(Debug, Default)]
+pub struct PartialWid {
+    pub timestamp: Option<DateTime<Utc>>,
+    pub sequence: Option<u64>,
+    pub padding: Option<String>,
+    /// Every problem found, in field order; empty when the WID is valid.
+    pub errors: Vec<WidError>,
+}
+
+/// Parse a WID field by field for diagnostics, collecting errors instead of
+/// failing fast.
+///
+/// A WID with a bad month still yields its sequence and padding. Only a
+/// missing `.` or `Z` separator stops extraction of the fields after it.
+pub fn parse_wid_partial(wid: &str, w: usize, z: usize, time_unit: TimeUnit) -> PartialWid {
+    let mut errors = Vec::new();
+    if w == 0 || w > MAX_W {
+        errors.push(WidError::InvalidW);
+        return PartialWid {
+            errors,
+            ..Default::default()
+        };
+    }
+    if z > MAX_Z {
+        errors.push(WidError::InvalidZ);
+        return PartialWid {
+            errors,
+            ..Default::default()
+        };
+    }
+    let all_digits = |s: &str| s.bytes().all(|b| b.is_ascii_digit());
+
+    let Some((ts, rest)) = wid.split_once('.') else {
+        errors.push(WidError::InvalidFormat(wid.to_string()));
+        return PartialWid {
+            errors,
+            ..Default::default()
+        };
+    };
+    let timestamp = match ts.split_once('T') {
+        Some((date, time))
+            if date.len() == 8
+                && time.len() == time_unit.time_digits()
+                && all_digits(date)
+                && all_digits(time) =>
+        {
+            let dt = parse_timestamp(time_unit, date, time);
+            if dt.is_none() {
+                errors.push(WidError::InvalidTimestamp);
+            }
+            dt
+        }
+        _ => {
+            errors.push(WidError::InvalidFormat(ts.to_string()));
+            None
+        }
+    };
+
+    let Some((seq, pad)) = rest.split_once('Z') else {
+        errors.push(WidError::InvalidFormat(rest.to_string()));
+        return PartialWid {
+            timestamp,
+            errors,
+            ..Default::default()
+        };
+    };
+    let sequence = match seq.parse::<u64>() {
+        Ok(n) if seq.len() == w && all_digits(seq) => Some(n),
+        _ => {
+            errors.push(WidError::InvalidFormat(seq.to_string()));
+            None
+        }
+    };
+
+    let padding = match pad.strip_prefix('-') {
+        None if pad.is_empty() => None,
+        Some(p) if z == 0 => {
+            errors.push(WidError::UnexpectedPadding(p.to_string()));
+            None
+        }
+        Some(p) if p.len() == z && p.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f')) => {
+            Some(p.to_string())
+        }
+        _ => {
+            errors.push(WidError::InvalidFormat(pad.to_string()));
+            None
+        }
+    };
+
+    PartialWid {
+        timestamp,
+        sequence,
+        padding,
+        errors,
+    }
+}
+
+/// WID generator with monotonic sequence and collision-resistant padding.
+pub struct WidGen {
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    max_seq: i64,
+    last_tick: i64,
+    last_seq: i64,
+    cached_tick: i64,
+    cached_ts: String,
+    descending: bool,
+    version_tag: Option<u8>,
+    shard: Option<(u32, usize)>,
+    padding: Box<dyn PaddingProvider>,
+    clock: Box<dyn Clock>,
+    /// Custom layout; `None` formats canonically without the extra
+    /// allocations a `WidFormat` call needs.
+    format: Option<Box<dyn WidFormat>>,
+    rate_tick: i64,
+    rate_count: u64,
+    throughput: f64,
+}
+
+impl WidGen {
+    /// Create a new WID generator in `sec` mode.
+    pub fn new(w: usize, z: usize, scope: Option<String>) -> Result<Self, WidError> {
+        Self::new_with_time_unit(w, z, scope, TimeUnit::Sec)
+    }
+
+    /// Create a new WID generator with a chosen time unit.
+    pub fn new_with_time_unit(
+        w: usize,
+        z: usize,
+        scope: Option<String>,
+        time_unit: TimeUnit,
+    ) -> Result<Self, WidError> {
+        // W > MAX_W would overflow the i64 sequence below (10^19 > i64::MAX).
+        check_generator_w(w)?;
+        if z > MAX_Z {
+            return Err(WidError::InvalidZ);
+        }
+
+        if scope.is_some() {
+            return Err(WidError::InvalidScope);
+        }
+
+        let max_seq = 10_i64.pow(w as u32) - 1;
+
+        Ok(Self {
+            w,
+            z,
+            time_unit,
+            max_seq,
+            last_tick: 0,
+            last_seq: -1,
+            cached_tick: -1,
+            cached_ts: String::new(),
+            descending: false,
+            version_tag: None,
+            shard: None,
+            padding: Box::new(HexPadding),
+            clock: Box::new(SystemClock(time_unit)),
+            format: None,
+            rate_tick: 0,
+            rate_count: 0,
+            throughput: 0.0,
+        })
+    }
+
+    /// Create a generator that reads ticks from `clock` instead of the system
+    /// time, e.g. a `ManualClock` in tests.
+    pub fn with_clock(
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        clock: Box<dyn Clock>,
+    ) -> Result<Self, WidError> {
+        let mut generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        generator.clock = clock;
+        Ok(generator)
+    }
+
+    /// Rebuild the generator that minted `manifest.wid` from the `wid.w`,
+    /// `wid.z` and `wid.time_unit` metadata written by
+    /// `Manifest::set_generator_config`, primed so its next ID follows that
+    /// WID. A missing `wid` or missing/mistyped key fails with
+    /// `InvalidManifestConfig` naming it.
+    pub fn from_manifest(manifest: &Manifest) -> Result<Self, WidError> {
+        let missing = |key: &str| WidError::InvalidManifestConfig(key.to_string());
+        let width = |key: &str| {
+            manifest
+                .metadata
+                .get(key)
+                .and_then(serde_json::Value::as_u64)
+                .and_then(|n| usize::try_from(n).ok())
+                .ok_or_else(|| missing(key))
+        };
+        let w = width(GENERATOR_W_KEY)?;
+        let z = width(GENERATOR_Z_KEY)?;
+        let time_unit = manifest
+            .metadata
+            .get(GENERATOR_TIME_UNIT_KEY)
+            .and_then(serde_json::Value::as_str)
+            .and_then(TimeUnit::parse)
+            .ok_or_else(|| missing(GENERATOR_TIME_UNIT_KEY))?;
+        let wid = manifest.wid.as_deref().ok_or_else(|| missing("wid"))?;
+
+        let parsed = parse_wid_with_unit(wid, w, z, time_unit)?;
+        Self::continuing_from(&parsed, w, z, time_unit)
+    }
+
+    /// Create a generator that draws padding from `provider` instead of the
+    /// thread-local RNG.
+    pub fn new_with_padding_provider(
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        provider: Box<dyn PaddingProvider>,
+    ) -> Result<Self, WidError> {
+        let mut generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        generator.padding = provider;
+        Ok(generator)
+    }
+
+    /// Create a generator that lays IDs out with `format` instead of the
+    /// canonical `TIMESTAMP.SEQZ-PAD`.
+    ///
+    /// Every ID from `next_wid` and friends goes through `format`; slots
+    /// handed out by `reserve_token` keep the canonical layout. The IDs no
+    /// longer pass `validate_wid` unless the format is canonical; parse them
+    /// with `parse_wid_with_format` and a matching `WidFormatParser`.
+    pub fn new_with_format(
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        format: Box<dyn WidFormat>,
+    ) -> Result<Self, WidError> {
+        let mut generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        generator.format = Some(format);
+        Ok(generator)
+    }
+
+    /// Create a generator whose IDs sort newest-first.
+    ///
+    /// Each `(tick, seq)` slot is written as `(MAX_TICK - tick, max_seq - seq)`,
+    /// where `MAX_TICK` is `MAX_TICK_SEC` or `MAX_TICK_MS` for the time unit, so
+    /// ascending lexicographic order is reverse chronological order. The IDs
+    /// still match the WID grammar; decode them with `parse_wid_descending`.
+    pub fn new_descending(w: usize, z: usize, time_unit: TimeUnit) -> Result<Self, WidError> {
+        let mut generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        generator.descending = true;
+        Ok(generator)
+    }
+
+    /// Create a generator whose sequences start with `shard`, zero-padded to
+    /// `shard_digits`, so IDs route to a shard by prefix.
+    ///
+    /// The low `w - shard_digits` digits count within the tick, so a tick
+    /// holds `10^(w - shard_digits)` IDs before borrowing the next one.
+    /// `shard_digits` must be in `1..w` and `shard` below `10^shard_digits`.
+    pub fn new_sharded(
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        shard: u32,
+        shard_digits: usize,
+    ) -> Result<Self, WidError> {
+        let mut generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        if shard_digits == 0
+            || shard_digits >= w
+            || u64::from(shard) >= 10_u64.pow(shard_digits as u32)
+        {
+            return Err(WidError::InvalidShard {
+                shard,
+                shard_digits,
+            });
+        }
+        generator.max_seq = 10_i64.pow((w - shard_digits) as u32) - 1;
+        generator.shard = Some((shard, shard_digits));
+        Ok(generator)
+    }
+
+    /// Create a generator that reserves the first padding character for a
+    /// format-version nibble.
+    ///
+    /// The remaining `z - 1` padding characters stay random. `version` must be
+    /// at most `0xf` and `z` at least 1. Read the tag back with
+    /// `extract_version_tag`.
+    pub fn new_with_version_tag(
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+        version: u8,
+    ) -> Result<Self, WidError> {
+        if version > 0xf {
+            return Err(WidError::InvalidVersionTag(version));
+        }
+        if z == 0 {
+            return Err(WidError::InvalidZ);
+        }
+        let mut generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        generator.version_tag = Some(version);
+        Ok(generator)
+    }
+
+    /// Create a generator that continues strictly after `parsed`.
+    ///
+    /// `parsed.raw` must be a valid WID for the given config, so an ID from a
+    /// differently shaped generator is rejected rather than silently resumed.
+    pub fn continuing_from(
+        parsed: &ParsedWid,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+    ) -> Result<Self, WidError> {
+        parse_wid_with_unit(&parsed.raw, w, z, time_unit)?;
+        let tick = time_unit
+            .tick_of(parsed.timestamp)
+            .ok_or(WidError::InvalidTimestamp)?;
+        let mut generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        // Parsed sequences have at most MAX_W digits, so they fit in i64.
+        generator.restore_state(tick, parsed.sequence as i64);
+        Ok(generator)
+    }
+
+    /// Format the WID for an exact `(tick, seq)` slot, e.g. to fill a known gap.
+    ///
+    /// No generator state is read or advanced, so the caller is responsible
+    /// for uniqueness: minting a slot a live generator also hands out yields a
+    /// duplicate (up to padding).
+    pub fn mint_exact(
+        tick: i64,
+        seq: u64,
+        w: usize,
+        z: usize,
+        time_unit: TimeUnit,
+    ) -> Result<String, WidError> {
+        let generator = Self::new_with_time_unit(w, z, None, time_unit)?;
+        let max = generator.max_seq as u64;
+        if seq > max {
+            return Err(WidError::SequenceOutOfRange { seq, max });
+        }
+        let secs = tick.div_euclid(time_unit.ticks_per_sec());
+        if !(0..=MAX_TICK_SEC).contains(&secs) {
+            return Err(WidError::InvalidTimestamp);
+        }
+        Ok(format_wid(
+            &format_tick(time_unit, tick),
+            seq as i64,
+            w,
+            z,
+            &mut HexPadding,
+        ))
+    }
+
+    /// Create a generator with default parameters (W=4, Z=6, `sec`).
+    pub fn default_params() -> Self {
+        Self::new(4, 6, None).expect("default parameters should always be valid")
+    }
+
+    fn ts_for_tick(&mut self, tick: i64) -> &str {
+        if tick != self.cached_tick {
+            self.cached_tick = tick;
+            self.cached_ts = format_tick(self.time_unit, tick);
+        }
+        &self.cached_ts
+    }
+
+    /// The next free `(tick, seq)` slot, without committing it.
+    fn next_slot(&self) -> (i64, i64) {
+        let now_tick = self.clock.now_tick();
+        let mut tick = if now_tick > self.last_tick {
+            now_tick
         } else {
             self.last_tick
         };
@@ -290,189 +1577,1760 @@ impl WidGen {
             seq = 0;
         }
 
+        (tick, seq)
+    }
+
+    /// Generate the next WID (domain API).
+    pub fn next_wid(&mut self) -> String {
+        self.next_wid_with(None)
+    }
+
+    /// Generate the next WID and record it in the process-wide registry.
+    ///
+    /// Fails with `DuplicateIssued` if any generator already issued the same
+    /// ID through this method. Behaves like `next_wid` until
+    /// `enable_global_uniqueness` is called.
+    pub fn next_unique_wid(&mut self) -> Result<String, WidError> {
+        let wid = self.next_wid();
+        crate::registry::claim(&wid)?;
+        Ok(wid)
+    }
+
+    /// `next_wid` with `padding` overriding the generator's own provider.
+    fn next_wid_with(&mut self, padding: Option<&mut dyn PaddingProvider>) -> String {
+        let (tick, seq) = self.next_slot();
+        self.emit_slot(tick, seq, padding).0
+    }
+
+    /// Like `next_wid`, but fails with `InvalidTimestamp` instead of
+    /// panicking or emitting a malformed ID when the next slot falls past
+    /// `MAX_TICK_SEC`/`MAX_TICK_MS`/`MAX_TICK_NS`, e.g. from a broken clock.
+    /// The generator state is left untouched on error.
+    pub fn try_next_wid(&mut self) -> Result<String, WidError> {
+        let (tick, seq) = self.next_slot();
+        if !(0..=self.time_unit.max_tick()).contains(&tick) {
+            return Err(WidError::InvalidTimestamp);
+        }
+        Ok(self.emit_slot(tick, seq, None).0)
+    }
+
+    /// Fallible counterpart of the `Iterator` impl, yielding `try_next_wid`
+    /// results. Like it, the iterator never ends.
+    pub fn try_iter(&mut self) -> TryIter<'_> {
+        TryIter { generator: self }
+    }
+
+    /// Commit `(tick, seq)` as the last issued slot and format its WID.
+    ///
+    /// Also returns the padding when a custom `format` placed it, since it
+    /// cannot be sliced back out of the ID; `None` for the canonical layout,
+    /// whose padding is the last `z` characters.
+    fn emit_slot(
+        &mut self,
+        tick: i64,
+        seq: i64,
+        padding: Option<&mut dyn PaddingProvider>,
+    ) -> (String, Option<String>) {
+        self.sample_throughput(tick);
         self.last_tick = tick;
         self.last_seq = seq;
 
-        let ts = self.ts_for_tick(tick).to_string();
-        let seq_str = format!("{:0width$}", seq, width = self.w);
+        let (tick, seq) = self.encode_slot(tick, seq);
+        let ts = self.ts_for_tick(tick).to_string();
+        let padding = match padding {
+            Some(padding) => padding,
+            None => &mut *self.padding,
+        };
+        let Some(format) = &self.format else {
+            let mut wid = format_wid(&ts, seq, self.w, self.z, padding);
+            if let Some(tag) = self.version_tag {
+                apply_version_tag(&mut wid, self.z, tag);
+            }
+            return (wid, None);
+        };
+        let mut pad = String::with_capacity(self.z);
+        if self.z > 0 {
+            padding.fill(&mut pad, self.z, HEX_ALPHABET);
+            if let Some(tag) = self.version_tag {
+                apply_version_tag(&mut pad, self.z, tag);
+            }
+        }
+        let seq = format!("{seq:0w$}", w = self.w);
+        let pad = (self.z > 0).then_some(pad);
+        (format.format(&ts, &seq, pad.as_deref()), pad)
+    }
+
+    /// The `(tick, seq)` actually written for a slot, after descending
+    /// inversion and the shard prefix.
+    fn encode_slot(&self, tick: i64, seq: i64) -> (i64, i64) {
+        let (tick, seq) = if self.descending {
+            invert_slot(self.time_unit, self.max_seq, tick, seq)
+        } else {
+            (tick, seq)
+        };
+        match self.shard {
+            Some((shard, _)) => (tick, i64::from(shard) * (self.max_seq + 1) + seq),
+            None => (tick, seq),
+        }
+    }
+
+    /// Like `next_wid`, but return the fields alongside the ID, built from the
+    /// slot rather than by parsing it back. They match what
+    /// `parse_wid_with_unit` (or `parse_wid_with_format`, for a generator from
+    /// `new_with_format`) reports for `raw`.
+    pub fn next_parsed(&mut self) -> ParsedWid {
+        let (tick, seq) = self.next_slot();
+        let (raw, custom_pad) = self.emit_slot(tick, seq, None);
+        let (tick, seq) = self.encode_slot(tick, seq);
+        ParsedWid {
+            timestamp: self
+                .time_unit
+                .datetime_of(tick)
+                .unwrap_or(DateTime::<Utc>::MAX_UTC),
+            sequence: seq as u64,
+            padding: custom_pad
+                .or_else(|| (self.z > 0).then(|| raw[raw.len() - self.z..].to_string())),
+            raw,
+        }
+    }
+
+    /// Count an ID issued at `tick`; when the tick moves on, fold the finished
+    /// tick's count into the moving average.
+    #[inline]
+    fn sample_throughput(&mut self, tick: i64) {
+        if tick != self.rate_tick {
+            let elapsed = (tick - self.rate_tick) as f64 / self.time_unit.ticks_per_sec() as f64;
+            if self.rate_count > 0 && elapsed > 0.0 {
+                let rate = self.rate_count as f64 / elapsed;
+                self.throughput += THROUGHPUT_ALPHA * (rate - self.throughput);
+            }
+            self.rate_tick = tick;
+            self.rate_count = 0;
+        }
+        self.rate_count += 1;
+    }
+
+    /// Exponentially weighted moving average of IDs issued per second,
+    /// sampled once per elapsed tick. Starts at 0 and only reflects ticks
+    /// that have finished, so a burst within the current tick shows up once
+    /// the clock moves on.
+    pub fn throughput_per_sec(&self) -> f64 {
+        self.throughput
+    }
+
+    /// Reserve `count` consecutive slots and advance past them.
+    ///
+    /// No later `next_wid` or reservation from this generator can overlap the
+    /// returned block. A zero-sized reservation leaves the state untouched.
+    ///
+    /// # Panics
+    ///
+    /// Panics where `try_reserve_token` fails.
+    pub fn reserve_token(&mut self, count: usize) -> Reservation {
+        self.try_reserve_token(count)
+            .expect("reservation runs past the last representable slot")
+    }
+
+    /// Like `reserve_token`, but fails with `ReservationOutOfRange`, leaving
+    /// the state untouched, when the block would run past the last
+    /// representable tick.
+    pub fn try_reserve_token(&mut self, count: usize) -> Result<Reservation, WidError> {
+        let (tick, start_seq) = self.next_slot();
+        let (last_tick, last_seq) = block_end(tick, start_seq, count, self.max_seq + 1)
+            .filter(|&(last_tick, _)| last_tick <= self.time_unit.max_tick())
+            .ok_or(WidError::ReservationOutOfRange(count))?;
+        if count > 0 {
+            self.last_tick = last_tick;
+            self.last_seq = last_seq;
+        }
+        Ok(Reservation {
+            tick,
+            start_seq,
+            count,
+            descending: self.descending,
+            version_tag: self.version_tag,
+            shard: self.shard,
+        })
+    }
+
+    /// Expand `token`, minted by this generator, using its own padding
+    /// provider instead of the thread-local RNG that `Reservation::iter` uses.
+    pub fn expand_reservation(&mut self, token: &Reservation) -> Result<Vec<String>, WidError> {
+        let (w, z, time_unit) = (self.w, self.z, self.time_unit);
+        Ok(token
+            .try_iter_with_padding(w, z, time_unit, &mut *self.padding)?
+            .collect())
+    }
+
+    /// Generate n WIDs.
+    pub fn next_n(&mut self, n: usize) -> Vec<String> {
+        self.take(n).collect()
+    }
+
+    /// Restore generator state.
+    pub fn restore_state(&mut self, last_tick: i64, last_seq: i64) {
+        self.last_tick = last_tick;
+        self.last_seq = last_seq;
+    }
+
+    /// Get current state.
+    pub fn state(&self) -> (i64, i64) {
+        (self.last_tick, self.last_seq)
+    }
+
+    /// Active time unit.
+    pub fn time_unit(&self) -> TimeUnit {
+        self.time_unit
+    }
+
+    /// Whether `other` would mint IDs in the same format: same `w`, `z`,
+    /// time unit, ordering, version tag, shard and layout. Custom layouts
+    /// cannot be compared, so two generators from `new_with_format` count as
+    /// equal in that respect; a custom and the canonical layout do not.
+    /// Transient state (last tick and sequence) and the padding source are
+    /// ignored.
+    pub fn config_eq(&self, other: &WidGen) -> bool {
+        self.format.is_some() == other.format.is_some()
+            && self.w == other.w
+            && self.z == other.z
+            && self.time_unit == other.time_unit
+            && self.descending == other.descending
+            && self.version_tag == other.version_tag
+            && self.shard == other.shard
+    }
+
+    /// Move the generator onto a background thread feeding a bounded channel.
+    ///
+    /// The producer blocks while the channel holds `capacity` IDs, and exits
+    /// once every receiver has been dropped. A single thread generates, so the
+    /// received IDs keep the generator's monotonic order.
+    #[cfg(feature = "crossbeam")]
+    pub fn spawn_producer(mut self, capacity: usize) -> crossbeam_channel::Receiver<String> {
+        let (tx, rx) = crossbeam_channel::bounded(capacity);
+        std::thread::spawn(move || while tx.send(self.next_wid()).is_ok() {});
+        rx
+    }
+}
+
+/// Config and sequence state only; the formatted-timestamp cache, padding
+/// provider and clock are omitted.
+impl std::fmt::Debug for WidGen {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WidGen")
+            .field("w", &self.w)
+            .field("z", &self.z)
+            .field("time_unit", &self.time_unit)
+            .field("last_tick", &self.last_tick)
+            .field("last_seq", &self.last_seq)
+            .field("descending", &self.descending)
+            .field("version_tag", &self.version_tag)
+            .field("shard", &self.shard)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for WidGen {
+    type Item = String;
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.next_wid())
+    }
+}
+
+/// Iterator returned by `WidGen::try_iter`.
+pub struct TryIter<'a> {
+    generator: &'a mut WidGen,
+}
+
+impl Iterator for TryIter<'_> {
+    type Item = Result<String, WidError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        Some(self.generator.try_next_wid())
+    }
+}
+
+/// Fluent configuration for `WidGen`, starting from W=4, Z=6, `sec`.
+///
+/// ```
+/// use wid::{TimeUnit, WidGenBuilder};
+///
+/// let mut wid_gen = WidGenBuilder::new()
+///     .width(6)
+///     .padding(8)
+///     .time_unit(TimeUnit::Ms)
+///     .build()
+///     .expect("valid params");
+/// let _ = wid_gen.next_wid();
+/// ```
+#[derive(Debug, Clone)]
+pub struct WidGenBuilder {
+    w: usize,
+    z: usize,
+    time_unit: TimeUnit,
+    seed: Option<u64>,
+    padding_mode: PaddingMode,
+}
+
+impl Default for WidGenBuilder {
+    fn default() -> Self {
+        Self {
+            w: 4,
+            z: 6,
+            time_unit: TimeUnit::Sec,
+            seed: None,
+            padding_mode: PaddingMode::PerChar,
+        }
+    }
+}
+
+impl WidGenBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Sequence width W.
+    pub fn width(mut self, w: usize) -> Self {
+        self.w = w;
+        self
+    }
+
+    /// Padding length Z in hex chars.
+    pub fn padding(mut self, z: usize) -> Self {
+        self.z = z;
+        self
+    }
+
+    pub fn time_unit(mut self, time_unit: TimeUnit) -> Self {
+        self.time_unit = time_unit;
+        self
+    }
+
+    /// Draw padding from an RNG seeded with `seed`, so the padding bytes
+    /// repeat across runs. Timestamps and sequences still follow the clock.
+    pub fn seed(mut self, seed: u64) -> Self {
+        self.seed = Some(seed);
+        self
+    }
+
+    /// How padding characters are drawn from the RNG; see `PaddingMode`.
+    pub fn padding_mode(mut self, mode: PaddingMode) -> Self {
+        self.padding_mode = mode;
+        self
+    }
+
+    /// Validate the parameters and create the generator.
+    pub fn build(self) -> Result<WidGen, WidError> {
+        let provider: Box<dyn PaddingProvider> = match (self.seed, self.padding_mode) {
+            (None, PaddingMode::PerChar) => {
+                return WidGen::new_with_time_unit(self.w, self.z, None, self.time_unit);
+            }
+            (Some(seed), PaddingMode::PerChar) => Box::new(RngPadding(StdRng::seed_from_u64(seed))),
+            (Some(seed), PaddingMode::Packed) => {
+                Box::new(PackedPadding(StdRng::seed_from_u64(seed)))
+            }
+            (None, PaddingMode::Packed) => Box::new(PackedPadding(rand::make_rng::<StdRng>())),
+        };
+        WidGen::new_with_padding_provider(self.w, self.z, self.time_unit, provider)
+    }
+}
+
+struct SharedState {
+    generator: WidGen,
+    base_seed: Option<u64>,
+    /// Per-thread padding RNGs of a seeded instance, in first-use order of
+    /// their threads. Owned here so dropping the instance frees all of them.
+    thread_rngs: HashMap<ThreadId, RngPadding<StdRng>>,
+}
+
+/// A `WidGen` that can be shared across threads by reference.
+///
+/// All threads draw from one sequence, so IDs stay unique and monotonic in
+/// the order the internal lock is acquired.
+pub struct SharedWidGen {
+    state: Mutex<SharedState>,
+}
+
+impl SharedWidGen {
+    /// Share `generator` with padding from the thread-local RNG.
+    pub fn new(generator: WidGen) -> Self {
+        Self::with_seed(generator, None)
+    }
+
+    /// Share a default-parameter generator (W=4, Z=6, `sec`) with
+    /// reproducible per-thread padding.
+    ///
+    /// Each thread gets its own RNG seeded with `base_seed ^ id`, where `id`
+    /// counts threads in the order they first call `next_wid` on this
+    /// instance. With the same base seed and the same thread scheduling, every
+    /// thread sees the same padding stream from run to run.
+    ///
+    /// The instance keeps one RNG per thread that has used it until it is
+    /// dropped, so prefer a long-lived pool over a thread per request.
+    pub fn new_seeded(base_seed: u64) -> Self {
+        Self::new_seeded_with(WidGen::default_params(), base_seed)
+    }
+
+    /// `new_seeded` for a custom `generator`.
+    pub fn new_seeded_with(generator: WidGen, base_seed: u64) -> Self {
+        Self::with_seed(generator, Some(base_seed))
+    }
+
+    fn with_seed(generator: WidGen, base_seed: Option<u64>) -> Self {
+        Self {
+            state: Mutex::new(SharedState {
+                generator,
+                base_seed,
+                thread_rngs: HashMap::new(),
+            }),
+        }
+    }
+
+    /// Generate the next WID.
+    pub fn next_wid(&self) -> String {
+        let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        let SharedState {
+            generator,
+            base_seed,
+            thread_rngs,
+        } = &mut *state;
+        let Some(base) = *base_seed else {
+            return generator.next_wid();
+        };
+        let id = thread_rngs.len() as u64;
+        let rng = thread_rngs
+            .entry(std::thread::current().id())
+            .or_insert_with(|| RngPadding(StdRng::seed_from_u64(base ^ id)));
+        generator.next_wid_with(Some(rng))
+    }
+
+    /// Get current state.
+    pub fn state(&self) -> (i64, i64) {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.generator.state()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_valid_wids() {
+        assert!(validate_wid("20260212T091530.0000Z", 4, 0));
+        assert!(validate_wid("20260212T091530.0042Z", 4, 0));
+        assert!(validate_wid("20260212T091530.0042Z-a3f91c", 4, 6));
+        assert!(validate_wid_with_unit(
+            "20260212T091530123.0042Z-a3f91c",
+            4,
+            6,
+            TimeUnit::Ms
+        ));
+    }
+
+    #[test]
+    fn test_validate_invalid_wids() {
+        assert!(!validate_wid("waldiez", 4, 6));
+        assert!(!validate_wid("20260212T091530.0000", 4, 0));
+        assert!(!validate_wid("20261312T091530.0000Z", 4, 0));
+        assert!(!validate_wid("20260212T091530.0000Z", 0, 0));
+        assert!(!validate_wid("20260212T091530.0000Z-ABCDEF", 4, 6));
+        assert!(!validate_wid("20260212T091530.0000Z-node01", 4, 0));
+        assert!(!validate_wid_with_unit(
+            "20260212T09153012.0000Z",
+            4,
+            0,
+            TimeUnit::Ms
+        ));
+    }
+
+    #[test]
+    fn test_new_rejects_invalid_params() {
+        assert!(matches!(WidGen::new(0, 0, None), Err(WidError::InvalidW)));
+        // W=19 used to panic (10_i64.pow(19) overflow); it must be a clean error.
+        assert_eq!(MAX_W, 18);
+        match WidGen::new(19, 0, None) {
+            Err(err @ WidError::WTooLarge { max_supported: 18 }) => {
+                assert!(err.to_string().contains("18"));
+            }
+            other => panic!("expected WTooLarge, got {:?}", other.map(|_| ())),
+        }
+        assert!(WidGen::new_with_time_unit(18, 0, None, TimeUnit::Ms).is_ok());
+        assert!(matches!(WidGen::new(4, 65, None), Err(WidError::InvalidZ)));
+        assert!(WidGen::new(18, 64, None).is_ok());
+        assert!(matches!(
+            WidGen::new(4, 0, Some("invalid scope".to_string())),
+            Err(WidError::InvalidScope)
+        ));
+    }
+
+    #[test]
+    fn test_generator_monotonic() {
+        let mut generator = WidGen::new(4, 0, None).expect("valid constructor args");
+        let wid1 = generator.next_wid();
+        let wid2 = generator.next_wid();
+        assert!(wid1 < wid2);
+    }
+
+    #[test]
+    fn test_iterator_take() {
+        let generator = WidGen::new(4, 6, None).unwrap();
+        let v: Vec<String> = generator.take(5).collect();
+        assert_eq!(v.len(), 5);
+    }
+
+    #[test]
+    fn test_parse_valid_wids() {
+        let p = parse_wid("20260212T091530.0042Z", 4, 0).unwrap();
+        assert_eq!(p.sequence, 42);
+        assert_eq!(p.padding, None);
+
+        let p2 = parse_wid("20260212T091530.0042Z-a3f91c", 4, 6).unwrap();
+        assert_eq!(p2.sequence, 42);
+        assert_eq!(p2.padding.as_deref(), Some("a3f91c"));
+        assert_eq!(p2.timestamp_sec(), p2.timestamp.timestamp());
+
+        let p3 =
+            parse_wid_with_unit("20260212T091530123.0042Z-a3f91c", 4, 6, TimeUnit::Ms).unwrap();
+        assert_eq!(p3.sequence, 42);
+        assert_eq!(p3.timestamp.timestamp_subsec_millis(), 123);
+    }
+
+    #[test]
+    fn test_parse_invalid_cases() {
+        assert!(matches!(
+            parse_wid("waldiez", 4, 6),
+            Err(WidError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            parse_wid("20260212T091530.0000Z-ABCDEF", 4, 6),
+            Err(WidError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            parse_wid("20260212T091530.0000Z-node01", 4, 0),
+            Err(WidError::InvalidFormat(_))
+        ));
+        assert!(matches!(
+            parse_wid("20260232T091530.0000Z", 4, 0),
+            Err(WidError::InvalidTimestamp)
+        ));
+        assert!(matches!(
+            parse_wid("20260212T091530.0000Z", 0, 0),
+            Err(WidError::InvalidW)
+        ));
+    }
+
+    #[test]
+    fn test_parse_timestamp_field_lengths() {
+        assert!(matches!(
+            parse_wid("2026212T091530.0000Z-a3f91c", 4, 6),
+            Err(WidError::InvalidTimestampLength {
+                expected: 8,
+                found: 7
+            })
+        ));
+        assert!(matches!(
+            parse_wid("202602120T091530.0000Z-a3f91c", 4, 6),
+            Err(WidError::InvalidTimestampLength {
+                expected: 8,
+                found: 9
+            })
+        ));
+        assert!(matches!(
+            parse_wid_with_unit("20260212T091530.0000Z", 4, 0, TimeUnit::Ms),
+            Err(WidError::InvalidTimestampLength {
+                expected: 9,
+                found: 6
+            })
+        ));
+    }
+
+    #[test]
+    fn test_parse_ref_borrows_and_agrees() {
+        let wid = "20260212T091530.0042Z-a3f91c";
+        let borrowed = parse_wid_ref(wid, 4, 6, TimeUnit::Sec).unwrap();
+        let padding = borrowed.padding.unwrap();
+        assert_eq!(padding.as_ptr(), wid[22..].as_ptr());
+        assert_eq!(borrowed.raw.as_ptr(), wid.as_ptr());
+
+        let owned = parse_wid(wid, 4, 6).unwrap();
+        assert_eq!(borrowed.timestamp, owned.timestamp);
+        assert_eq!(borrowed.sequence, owned.sequence);
+        assert_eq!(borrowed.padding, owned.padding.as_deref());
+        assert_eq!(borrowed.into_owned(), owned);
+        assert!(parse_wid_ref("bad", 4, 6, TimeUnit::Sec).is_err());
+    }
+
+    #[test]
+    fn test_parse_wid_bounded() {
+        let unit = TimeUnit::Sec;
+        let ok = parse_wid_bounded("20260212T091530.0000Z-a3f91c", 4, 6, unit, 2100).unwrap();
+        assert_eq!(ok.timestamp.year(), 2026);
+        assert!(matches!(
+            parse_wid_bounded("99991231T235959.0000Z-a3f91c", 4, 6, unit, 2100),
+            Err(WidError::TimestampOutOfRange {
+                year: 9999,
+                min: 2000,
+                max: 2100
+            })
+        ));
+        assert!(matches!(
+            parse_wid_bounded("19991231T235959.0000Z-a3f91c", 4, 6, unit, 2100),
+            Err(WidError::TimestampOutOfRange { year: 1999, .. })
+        ));
+        assert!(parse_wid("99991231T235959.0000Z-a3f91c", 4, 6).is_ok());
+    }
+
+    #[test]
+    fn test_parse_empty_input() {
+        assert!(matches!(parse_wid("", 4, 6), Err(WidError::Empty)));
+        assert!(matches!(parse_wid("   ", 4, 6), Err(WidError::Empty)));
+        assert!(matches!(
+            parse_wid(" x ", 4, 6),
+            Err(WidError::InvalidFormat(_))
+        ));
+    }
+
+    #[test]
+    fn test_parse_partial_keeps_fields_on_bad_month() {
+        let p = parse_wid_partial("20261312T091530.0042Z-a3f91c", 4, 6, TimeUnit::Sec);
+        assert_eq!(p.timestamp, None);
+        assert_eq!(p.sequence, Some(42));
+        assert_eq!(p.padding.as_deref(), Some("a3f91c"));
+        assert_eq!(p.errors.len(), 1);
+        assert!(matches!(p.errors[0], WidError::InvalidTimestamp));
+
+        let ok = parse_wid_partial("20260212T091530.0042Z-a3f91c", 4, 6, TimeUnit::Sec);
+        assert!(ok.errors.is_empty());
+        assert_eq!(
+            ok.timestamp,
+            Some(parse_wid("20260212T091530.0042Z", 4, 0).unwrap().timestamp)
+        );
+
+        let bad = parse_wid_partial("20261312T091530.00x2Z-ABC", 4, 6, TimeUnit::Sec);
+        assert_eq!(bad.errors.len(), 3);
+        assert_eq!((bad.sequence, bad.padding), (None, None));
+    }
+
+    #[test]
+    fn test_debug_hides_caches() {
+        let mut wid_gen = WidGen::new(4, 6, None).unwrap();
+        wid_gen.restore_state(1_770_888_930, 7);
+        let out = format!("{wid_gen:?}");
+        for field in [
+            "w: 4",
+            "z: 6",
+            "time_unit: Sec",
+            "last_tick: 1770888930",
+            "last_seq: 7",
+        ] {
+            assert!(out.contains(field), "{out}");
+        }
+        assert!(!out.contains("cached"), "{out}");
+        assert!(!out.contains("padding"), "{out}");
+    }
+
+    #[test]
+    fn test_try_iter_yields_results() {
+        let mut wid_gen = WidGen::new(4, 6, None).unwrap();
+        let ids: Vec<_> = wid_gen.try_iter().take(3).collect();
+        assert_eq!(ids.len(), 3);
+        assert!(
+            ids.iter()
+                .all(|id| id.as_ref().is_ok_and(|id| validate_wid(id, 4, 6)))
+        );
+
+        let clock = Arc::new(ManualClock::new(MAX_TICK_SEC + 1));
+        let mut broken = WidGen::with_clock(4, 6, TimeUnit::Sec, Box::new(clock.clone())).unwrap();
+        assert!(matches!(
+            broken.try_iter().next(),
+            Some(Err(WidError::InvalidTimestamp))
+        ));
+        assert_eq!(broken.state(), (0, -1));
+        clock.set(MAX_TICK_SEC);
+        assert!(
+            broken
+                .try_next_wid()
+                .unwrap()
+                .starts_with("99991231T235959.0000Z")
+        );
+    }
+
+    #[test]
+    fn test_manual_clock_rollover_and_backwards() {
+        let clock = Arc::new(ManualClock::new(1_770_888_930));
+        let mut wid_gen = WidGen::with_clock(1, 0, TimeUnit::Sec, Box::new(clock.clone())).unwrap();
+        let ids = wid_gen.next_n(12);
+        assert_eq!(ids[0], "20260212T093530.0Z");
+        assert_eq!(ids[9], "20260212T093530.9Z");
+        // Sequence saturated: borrow the next tick while the clock stands still.
+        assert_eq!(ids[10], "20260212T093531.0Z");
+        assert_eq!(ids[11], "20260212T093531.1Z");
+
+        // A clock that jumps back is clamped to the last issued tick.
+        clock.set(1_770_888_000);
+        assert_eq!(wid_gen.next_wid(), "20260212T093531.2Z");
+
+        clock.set(1_770_888_940);
+        assert_eq!(wid_gen.next_wid(), "20260212T093540.0Z");
+        clock.advance(1);
+        assert_eq!(wid_gen.next_wid(), "20260212T093541.0Z");
+    }
+
+    #[test]
+    fn test_from_manifest_continues_sequence() {
+        let wid = "30000101T000000.0041Z-a3f91c";
+        let mut file =
+            crate::manifest::SynapseFile::for_wid(wid, 4, 6, TimeUnit::Sec, b"{}".to_vec())
+                .unwrap();
+        assert!(matches!(
+            WidGen::from_manifest(&file.manifest),
+            Err(WidError::InvalidManifestConfig(key)) if key == GENERATOR_W_KEY
+        ));
+
+        file.manifest.set_generator_config(4, 6, TimeUnit::Sec);
+        let mut wid_gen = WidGen::from_manifest(&file.manifest).unwrap();
+        assert_eq!(wid_gen.state(), (32_503_680_000, 41));
+        assert!(wid_gen.next_wid().starts_with("30000101T000000.0042Z-"));
+
+        file.manifest.wid = None;
+        assert!(matches!(
+            WidGen::from_manifest(&file.manifest),
+            Err(WidError::InvalidManifestConfig(key)) if key == "wid"
+        ));
+    }
+
+    #[test]
+    fn test_sharded_sequence_prefix_and_rollover() {
+        let clock = Arc::new(ManualClock::new(1_770_888_930));
+        let mut wid_gen = WidGen::new_sharded(4, 0, TimeUnit::Sec, 7, 1).unwrap();
+        wid_gen.clock = Box::new(clock.clone());
+        let ids = wid_gen.next_n(1001);
+        assert_eq!(ids[0], "20260212T093530.7000Z");
+        assert_eq!(ids[999], "20260212T093530.7999Z");
+        // Only 1000 slots per tick remain once a digit goes to the shard.
+        assert_eq!(ids[1000], "20260212T093531.7000Z");
+
+        let token = wid_gen.reserve_token(2);
+        let reserved: Vec<String> = token.iter(4, 0, TimeUnit::Sec).collect();
+        assert_eq!(reserved, ["20260212T093531.7001Z", "20260212T093531.7002Z"]);
+        assert_eq!(wid_gen.next_wid(), "20260212T093531.7003Z");
+
+        for (shard, digits) in [(10, 1), (0, 0), (0, 4)] {
+            assert!(matches!(
+                WidGen::new_sharded(4, 0, TimeUnit::Sec, shard, digits),
+                Err(WidError::InvalidShard { .. })
+            ));
+        }
+        let two_digit = WidGen::new_sharded(4, 0, TimeUnit::Sec, 42, 2).unwrap();
+        assert!(!two_digit.config_eq(&wid_gen));
+    }
+
+    #[test]
+    fn test_next_parsed_matches_next_wid() {
+        let seeded = || {
+            let mut generator = WidGenBuilder::new().seed(9).build().unwrap();
+            generator.clock = Box::new(ManualClock::new(1_770_888_930));
+            generator
+        };
+        let (mut a, mut b) = (seeded(), seeded());
+        for _ in 0..3 {
+            let parsed = a.next_parsed();
+            assert_eq!(parsed.raw, b.next_wid());
+            assert_eq!(parsed, parse_wid(&parsed.raw, 4, 6).unwrap());
+        }
+
+        let mut sharded = WidGen::new_sharded(4, 0, TimeUnit::Ms, 3, 1).unwrap();
+        let parsed = sharded.next_parsed();
+        assert_eq!(parsed.padding, None);
+        assert_eq!(
+            parsed,
+            parse_wid_with_unit(&parsed.raw, 4, 0, TimeUnit::Ms).unwrap()
+        );
+    }
+
+    #[test]
+    fn test_throughput_tracks_bursts() {
+        let clock = Arc::new(ManualClock::new(1_770_888_930));
+        let mut wid_gen = WidGen::with_clock(4, 0, TimeUnit::Sec, Box::new(clock.clone())).unwrap();
+        assert_eq!(wid_gen.throughput_per_sec(), 0.0);
+
+        wid_gen.next_n(500);
+        assert_eq!(wid_gen.throughput_per_sec(), 0.0);
+        clock.advance(1);
+        wid_gen.next_wid();
+        let rate = wid_gen.throughput_per_sec();
+        assert!(rate > 0.0);
+        assert!((rate - 500.0 * THROUGHPUT_ALPHA).abs() < 1e-9);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde_round_trip() {
+        let parsed = parse_wid("20260212T091530.0042Z-a3f91c", 4, 6).unwrap();
+        let json = serde_json::to_value(&parsed).unwrap();
+        assert_eq!(json["timestamp"], "2026-02-12T09:15:30Z");
+        assert_eq!(json["sequence"], 42);
+        assert_eq!(json["padding"], "a3f91c");
+        let back: ParsedWid = serde_json::from_value(json).unwrap();
+        assert_eq!(back, parsed);
+
+        let unpadded = parse_wid("20260212T091530.0042Z", 4, 0).unwrap();
+        assert!(serde_json::to_value(&unpadded).unwrap()["padding"].is_null());
+
+        for (unit, name) in [
+            (TimeUnit::Sec, "\"sec\""),
+            (TimeUnit::Ms, "\"ms\""),
+            (TimeUnit::Ns, "\"ns\""),
+        ] {
+            assert_eq!(serde_json::to_string(&unit).unwrap(), name);
+            assert_eq!(serde_json::from_str::<TimeUnit>(name).unwrap(), unit);
+        }
+    }
+
+    #[test]
+    fn test_validate_wid_in_window() {
+        let minute = Duration::from_secs(60);
+        let skew = Duration::from_secs(5);
+        let now = Utc::now().timestamp();
+        let fresh = WidGen::mint_exact(now - 10, 0, 4, 6, TimeUnit::Sec).unwrap();
+        assert!(validate_wid_in_window(
+            &fresh,
+            4,
+            6,
+            TimeUnit::Sec,
+            minute,
+            skew
+        ));
+        assert!(!validate_wid_in_window(
+            &fresh,
+            4,
+            6,
+            TimeUnit::Sec,
+            skew,
+            skew
+        ));
+
+        let ahead = WidGen::mint_exact(now + 30, 0, 4, 6, TimeUnit::Sec).unwrap();
+        assert!(!validate_wid_in_window(
+            &ahead,
+            4,
+            6,
+            TimeUnit::Sec,
+            minute,
+            skew
+        ));
+        assert!(validate_wid_in_window(
+            &ahead,
+            4,
+            6,
+            TimeUnit::Sec,
+            minute,
+            minute
+        ));
+
+        let old = "20200101T000000.0000Z";
+        assert!(!validate_wid_in_window(
+            old,
+            4,
+            0,
+            TimeUnit::Sec,
+            minute,
+            skew
+        ));
+        assert!(validate_wid_in_window(
+            old,
+            4,
+            0,
+            TimeUnit::Sec,
+            Duration::MAX,
+            skew
+        ));
+        assert!(!validate_wid_in_window(
+            "bogus",
+            4,
+            0,
+            TimeUnit::Sec,
+            Duration::MAX,
+            Duration::MAX
+        ));
+    }
+
+    #[test]
+    fn test_elapsed_and_freshness() {
+        let old = parse_wid("20200101T000000.0000Z", 4, 0).unwrap();
+        assert!(old.elapsed() > chrono::Duration::days(365));
+        assert!(old.age_secs() > 365 * 86_400);
+        assert!(old.is_older_than(60));
+        assert!(!old.is_older_than(u64::MAX));
+        assert!(!old.is_older_than(i64::MAX as u64 / 10));
+
+        // A few seconds of skew puts the ID in the future: negative age.
+        let ahead = Utc::now().timestamp() + 30;
+        let wid = WidGen::mint_exact(ahead, 0, 4, 0, TimeUnit::Sec).unwrap();
+        let skewed = parse_wid(&wid, 4, 0).unwrap();
+        // Sub-second truncation and a slow test run shave off up to ~2s.
+        assert!((-30..=-27).contains(&skewed.age_secs()));
+        assert!(skewed.elapsed() < chrono::Duration::zero());
+        assert!(!skewed.is_older_than(0));
+    }
+
+    #[test]
+    fn test_coarsen_minute_and_hour() {
+        let wid = "20260212T091530.0042Z-a3f91c";
+        let minute = coarsen(wid, Granularity::Minute, 4, 6, TimeUnit::Sec).unwrap();
+        assert_eq!(minute, "20260212T091500.0000Z-a3f91c");
+        let hour = coarsen(wid, Granularity::Hour, 4, 6, TimeUnit::Sec).unwrap();
+        assert_eq!(hour, "20260212T090000.0000Z-a3f91c");
+        assert!(validate_wid(&hour, 4, 6));
+
+        let ms = "20260212T091530123.0042Z";
+        assert_eq!(
+            coarsen(ms, Granularity::Second, 4, 0, TimeUnit::Ms).unwrap(),
+            "20260212T091530000.0000Z"
+        );
+        assert_eq!(
+            coarsen(ms, Granularity::Day, 4, 0, TimeUnit::Ms).unwrap(),
+            "20260212T000000000.0000Z"
+        );
+        assert!(coarsen("bogus", Granularity::Hour, 4, 0, TimeUnit::Sec).is_err());
+    }
+
+    #[test]
+    fn test_ns_unit_round_trip_and_monotonic() {
+        let id = "20260212T091530123456789.0042Z-a3f91c";
+        let parsed = parse_wid_with_unit(id, 4, 6, TimeUnit::Ns).unwrap();
+        assert_eq!(parsed.timestamp.timestamp_subsec_nanos(), 123_456_789);
+        assert!(parse_wid_with_unit(id, 4, 6, TimeUnit::Ms).is_err());
+        // Past MAX_TICK_NS the instant has no i64 nanosecond tick.
+        assert!(matches!(
+            parse_wid_with_unit("22620411T234716854775808.0000Z", 4, 0, TimeUnit::Ns),
+            Err(WidError::InvalidTimestamp)
+        ));
+        assert!(parse_wid_with_unit("22620411T234716854775807.0000Z", 4, 0, TimeUnit::Ns).is_ok());
+
+        let (ms, lossy) = convert_time_unit(id, 4, 6, TimeUnit::Ns, TimeUnit::Ms).unwrap();
+        assert_eq!(ms, "20260212T091530123.0042Z-a3f91c");
+        assert!(lossy);
+        let (ns, lossy) = convert_time_unit(&ms, 4, 6, TimeUnit::Ms, TimeUnit::Ns).unwrap();
+        assert_eq!(ns, "20260212T091530123000000.0042Z-a3f91c");
+        assert!(!lossy);
+
+        // A burst inside one millisecond still sorts strictly increasing.
+        let mut wid_gen = WidGen::new_with_time_unit(4, 0, None, TimeUnit::Ns).unwrap();
+        let ids = wid_gen.next_n(50);
+        assert!(ids.windows(2).any(|pair| pair[0][..18] == pair[1][..18]));
+        assert!(ids.windows(2).all(|pair| pair[0] < pair[1]));
+        for id in &ids {
+            assert!(validate_wid_with_unit(id, 4, 0, TimeUnit::Ns));
+        }
+    }
+
+    #[test]
+    fn test_state_restore_and_next_n() {
+        let mut g1 = WidGen::new(4, 0, None).unwrap();
+        let _ = g1.next_wid();
+        let _ = g1.next_wid();
+        let (last_tick, last_seq) = g1.state();
+
+        let mut g2 = WidGen::new(4, 0, None).unwrap();
+        g2.restore_state(last_tick, last_seq);
+        let w = g2.next_wid();
+        let p = parse_wid(&w, 4, 0).unwrap();
+        assert_eq!(p.sequence as i64, last_seq + 1);
+
+        let v = g2.next_n(3);
+        assert_eq!(v.len(), 3);
+    }
 
-        let mut wid = format!("{ts}.{seq_str}Z");
+    #[test]
+    fn test_default_params_and_scope_rejected() {
+        let mut g = WidGen::default_params();
+        let w = g.next_wid();
+        assert!(validate_wid(&w, 4, 6));
+        assert!(matches!(
+            WidGen::new(4, 6, Some("acme".to_string())),
+            Err(WidError::InvalidScope)
+        ));
+    }
 
-        if self.z > 0 {
-            const HEX: &[u8; 16] = b"0123456789abcdef";
-            let pad: String = (0..self.z).map(|_| HEX[random_range(0..16)] as char).collect();
-            wid.push('-');
-            wid.push_str(&pad);
-        }
+    #[test]
+    fn test_ms_generator_shape() {
+        let mut g = WidGen::new_with_time_unit(4, 0, None, TimeUnit::Ms).unwrap();
+        let w = g.next_wid();
+        assert!(validate_wid_with_unit(&w, 4, 0, TimeUnit::Ms));
+    }
 
-        wid
+    #[cfg(feature = "crossbeam")]
+    #[test]
+    fn test_spawn_producer_is_monotonic() {
+        let rx = WidGen::new(4, 0, None).unwrap().spawn_producer(8);
+        let ids: Vec<String> = rx.iter().take(100).collect();
+        assert_eq!(ids.len(), 100);
+        assert!(ids.windows(2).all(|p| p[0] < p[1]));
     }
 
-    /// Generate n WIDs.
-    pub fn next_n(&mut self, n: usize) -> Vec<String> {
-        self.take(n).collect()
+    #[test]
+    fn test_next_second_boundary() {
+        let sec = parse_wid("20260212T091530.0042Z-a3f91c", 4, 6).unwrap();
+        let next = next_second_boundary(&sec, 4, 6, TimeUnit::Sec);
+        assert_eq!(next, "20260212T091531.0000Z-000000");
+        assert_eq!(
+            parse_wid(&next, 4, 6).unwrap().timestamp_sec(),
+            sec.timestamp_sec() + 1
+        );
+
+        let ms = parse_wid_with_unit("20260212T235959987.0042Z", 4, 0, TimeUnit::Ms).unwrap();
+        let next = next_second_boundary(&ms, 4, 0, TimeUnit::Ms);
+        assert_eq!(next, "20260213T000000000.0000Z");
+        let parsed = parse_wid_with_unit(&next, 4, 0, TimeUnit::Ms).unwrap();
+        assert_eq!(parsed.timestamp.timestamp_subsec_millis(), 0);
+        assert_eq!(parsed.timestamp_sec(), ms.timestamp_sec() + 1);
     }
 
-    /// Restore generator state.
-    pub fn restore_state(&mut self, last_tick: i64, last_seq: i64) {
-        self.last_tick = last_tick;
-        self.last_seq = last_seq;
+    #[test]
+    fn test_to_columns_aligns_rows() {
+        let parsed = vec![
+            parse_wid("20260212T091530.0001Z-a3f91c", 4, 6).unwrap(),
+            parse_wid("20260212T091530.0002Z", 4, 6).unwrap(),
+            parse_wid_with_unit("20260212T091531250.0007Z-00ff00", 4, 6, TimeUnit::Ms).unwrap(),
+        ];
+        let cols = to_columns(&parsed);
+        assert_eq!(cols.timestamps.len(), 3);
+        assert_eq!(cols.sequences, vec![1, 2, 7]);
+        assert_eq!(cols.paddings.len(), 3);
+        assert_eq!(cols.paddings[1], None);
+        assert_eq!(cols.paddings[2].as_deref(), Some("00ff00"));
+        assert_eq!(cols.timestamps[2] - cols.timestamps[0], 1250);
+        assert_eq!(cols.sub_ms_nanos, vec![0, 0, 0]);
+        assert_eq!(to_columns(&[]), WidColumns::default());
+
+        let ns = parse_wid_with_unit("20260212T091531250123456.0001Z", 4, 0, TimeUnit::Ns).unwrap();
+        let cols = to_columns(&[parsed[0].clone(), ns]);
+        assert_eq!(cols.timestamps[1] - cols.timestamps[0], 1250);
+        assert_eq!(cols.sub_ms_nanos[1], 123_456);
     }
 
-    /// Get current state.
-    pub fn state(&self) -> (i64, i64) {
-        (self.last_tick, self.last_seq)
+    #[test]
+    fn test_continuing_from_follows_parsed() {
+        let last = "29991231T235959.0042Z-a3f91c";
+        let parsed = parse_wid(last, 4, 6).unwrap();
+        let mut wid_gen = WidGen::continuing_from(&parsed, 4, 6, TimeUnit::Sec).unwrap();
+        let next = wid_gen.next_wid();
+        assert!(next.as_str() > last);
+        assert!(next.starts_with("29991231T235959.0043Z-"));
+
+        assert!(WidGen::continuing_from(&parsed, 5, 6, TimeUnit::Sec).is_err());
+        assert!(WidGen::continuing_from(&parsed, 4, 6, TimeUnit::Ms).is_err());
+
+        let ms = parse_wid_with_unit("29991231T235959123.9999Z", 4, 0, TimeUnit::Ms).unwrap();
+        let mut wid_gen = WidGen::continuing_from(&ms, 4, 0, TimeUnit::Ms).unwrap();
+        assert_eq!(wid_gen.next_wid(), "29991231T235959124.0000Z");
     }
 
-    /// Active time unit.
-    pub fn time_unit(&self) -> TimeUnit {
-        self.time_unit
+    #[test]
+    fn test_min_w_for_rate() {
+        assert_eq!(min_w_for_rate(5000, TimeUnit::Sec), 4);
+        assert_eq!(min_w_for_rate(50_000, TimeUnit::Sec), 5);
+        assert_eq!(min_w_for_rate(5000, TimeUnit::Ms), 1);
+        assert_eq!(min_w_for_rate(10_000, TimeUnit::Sec), 4);
+        assert_eq!(min_w_for_rate(10_001, TimeUnit::Sec), 5);
+        assert_eq!(min_w_for_rate(0, TimeUnit::Sec), 1);
     }
-}
 
-impl Iterator for WidGen {
-    type Item = String;
+    #[test]
+    fn test_renumber_preserves_order_with_bijective_map() {
+        let ids = [
+            "20260212T091531.0000Z",
+            "20260212T091530.0007Z",
+            "not-a-wid",
+            "20260212T091530.0002Z",
+            "20260212T091530.0007Z",
+        ];
+        let (fresh, mapping) = renumber(&ids, 4, 0, TimeUnit::Sec, 1_770_888_930);
+        assert_eq!(
+            fresh,
+            [
+                "20260212T093530.0000Z",
+                "20260212T093530.0001Z",
+                "20260212T093530.0002Z"
+            ]
+        );
+        // The unparseable input is skipped, not assigned a slot.
+        assert_eq!(mapping.len(), 3);
+        assert!(!mapping.contains_key("not-a-wid"));
+        assert_eq!(mapping["20260212T091530.0002Z"], fresh[0]);
+        assert_eq!(mapping["20260212T091530.0007Z"], fresh[1]);
+        assert_eq!(mapping["20260212T091531.0000Z"], fresh[2]);
+        let targets: std::collections::HashSet<&String> = mapping.values().collect();
+        assert_eq!(targets.len(), mapping.len());
 
-    #[inline]
-    fn next(&mut self) -> Option<Self::Item> {
-        Some(self.next_wid())
+        // Eleven IDs with W=1 roll into the next tick.
+        let many: Vec<String> = (0..11).map(|i| format!("20260212T0915{i:02}.0Z")).collect();
+        let refs: Vec<&str> = many.iter().map(String::as_str).collect();
+        let (fresh, _) = renumber(&refs, 1, 0, TimeUnit::Sec, 1_770_888_930);
+        assert_eq!(fresh[10], "20260212T093531.0Z");
+        assert!(renumber(&refs, 0, 0, TimeUnit::Sec, 0).0.is_empty());
     }
-}
-
-#[cfg(test)]
-mod tests {
-    use super::*;
 
     #[test]
-    fn test_validate_valid_wids() {
-        assert!(validate_wid("20260212T091530.0000Z", 4, 0));
-        assert!(validate_wid("20260212T091530.0042Z", 4, 0));
-        assert!(validate_wid("20260212T091530.0042Z-a3f91c", 4, 6));
-        assert!(validate_wid_with_unit(
-            "20260212T091530123.0042Z-a3f91c",
-            4,
-            6,
-            TimeUnit::Ms
-        ));
+    fn test_capture_fields_slices() {
+        let fields = capture_fields("20260212T091530123.0042Z-a3f91c", 4, 6, TimeUnit::Ms).unwrap();
+        assert_eq!(
+            fields,
+            WidFields {
+                date: "20260212",
+                time: "091530123",
+                sequence: "0042",
+                padding: Some("a3f91c"),
+            }
+        );
+        // Shape only: month 13 still captures.
+        let fields = capture_fields("20261312T091530.0042Z", 4, 6, TimeUnit::Sec).unwrap();
+        assert_eq!((fields.date, fields.padding), ("20261312", None));
+        assert!(capture_fields("20260212T091530.042Z", 4, 6, TimeUnit::Sec).is_none());
+        assert!(capture_fields("20260212T091530.0042Z", 0, 6, TimeUnit::Sec).is_none());
     }
 
     #[test]
-    fn test_validate_invalid_wids() {
-        assert!(!validate_wid("waldiez", 4, 6));
-        assert!(!validate_wid("20260212T091530.0000", 4, 0));
-        assert!(!validate_wid("20261312T091530.0000Z", 4, 0));
-        assert!(!validate_wid("20260212T091530.0000Z", 0, 0));
-        assert!(!validate_wid("20260212T091530.0000Z-ABCDEF", 4, 6));
-        assert!(!validate_wid("20260212T091530.0000Z-node01", 4, 0));
-        assert!(!validate_wid_with_unit(
-            "20260212T09153012.0000Z",
-            4,
-            0,
-            TimeUnit::Ms
+    fn test_before_cutover() {
+        let cutover = Utc.with_ymd_and_hms(2026, 2, 12, 9, 15, 30).unwrap();
+        let unit = TimeUnit::Ms;
+        assert!(before("20260212T091529999.0000Z", cutover, 4, 0, unit).unwrap());
+        assert!(!before("20260212T091530000.0000Z", cutover, 4, 0, unit).unwrap());
+        assert!(!before("20260212T091530001.0000Z", cutover, 4, 0, unit).unwrap());
+        assert!(matches!(
+            before("not-a-wid", cutover, 4, 0, unit),
+            Err(WidError::InvalidFormat(_))
         ));
     }
 
     #[test]
-    fn test_new_rejects_invalid_params() {
-        assert!(matches!(WidGen::new(0, 0, None), Err(WidError::InvalidW)));
-        // W=19 used to panic (10_i64.pow(19) overflow); it must be a clean error.
-        assert!(matches!(WidGen::new(19, 0, None), Err(WidError::InvalidW)));
-        assert!(matches!(WidGen::new(4, 65, None), Err(WidError::InvalidZ)));
-        assert!(WidGen::new(18, 64, None).is_ok());
+    fn test_parse_batch_partitions_results() {
+        let ids = [
+            "20260212T091530.0000Z-a3f91c",
+            "not-a-wid",
+            "20260212T091530.0001Z",
+            "",
+            "2026021T091530.0002Z",
+            "20260212T091531.0000Z-000001",
+        ];
+        let (parsed, failures) = parse_batch(&ids, 4, 6, TimeUnit::Sec);
+        let raw: Vec<&str> = parsed.iter().map(|p| p.raw.as_str()).collect();
+        assert_eq!(raw, [ids[0], ids[2], ids[5]]);
+        let indices: Vec<usize> = failures.iter().map(|(i, _)| *i).collect();
+        assert_eq!(indices, [1, 3, 4]);
+        assert!(matches!(failures[0].1, WidError::InvalidFormat(_)));
+        assert!(matches!(failures[1].1, WidError::Empty));
         assert!(matches!(
-            WidGen::new(4, 0, Some("invalid scope".to_string())),
-            Err(WidError::InvalidScope)
+            failures[2].1,
+            WidError::InvalidTimestampLength {
+                expected: 8,
+                found: 7
+            }
         ));
+
+        let (parsed, failures) = parse_batch(&ids[..2], 0, 6, TimeUnit::Sec);
+        assert!(parsed.is_empty());
+        assert_eq!(failures.len(), 2);
+        assert!(
+            failures
+                .iter()
+                .all(|(_, e)| matches!(e, WidError::InvalidW))
+        );
+        let (_, failures) = parse_batch(&ids[..2], 4, 99, TimeUnit::Sec);
+        assert!(
+            failures
+                .iter()
+                .all(|(_, e)| matches!(e, WidError::InvalidZ))
+        );
     }
 
     #[test]
-    fn test_generator_monotonic() {
-        let mut generator = WidGen::new(4, 0, None).expect("valid constructor args");
-        let wid1 = generator.next_wid();
-        let wid2 = generator.next_wid();
-        assert!(wid1 < wid2);
+    fn test_stable_sort_padding_tiebreak() {
+        let mut ids = vec![
+            "not-a-wid".to_string(),
+            "20260212T091531.0000Z-000001".to_string(),
+            "20260212T091530.0001Z-00000a".to_string(),
+            "20260212T091530.0001Z-000009".to_string(),
+            "20260212T091530.0001Z-0000ff".to_string(),
+            "20260212T091530.0000Z-ffffff".to_string(),
+            "also-not".to_string(),
+        ];
+        stable_sort(&mut ids, 4, 6, TimeUnit::Sec);
+        assert_eq!(
+            ids,
+            [
+                "20260212T091530.0000Z-ffffff",
+                "20260212T091530.0001Z-000009",
+                "20260212T091530.0001Z-00000a",
+                "20260212T091530.0001Z-0000ff",
+                "20260212T091531.0000Z-000001",
+                "also-not",
+                "not-a-wid",
+            ]
+        );
+
+        let a = parse_wid("20260212T091530.0001Z-000009", 4, 6).unwrap();
+        let b = parse_wid("20260212T091530.0001Z-00000a", 4, 6).unwrap();
+        assert!(a < b);
+        assert_eq!(a.cmp(&a.clone()), std::cmp::Ordering::Equal);
+
+        // Across widths the string order inverts; the parsed order does not.
+        let narrow = parse_wid("20260212T091530.0009Z", 4, 0).unwrap();
+        let wide = parse_wid("20260212T091530.00010Z", 5, 0).unwrap();
+        assert!(wide.raw < narrow.raw);
+        assert!(narrow < wide);
     }
 
     #[test]
-    fn test_iterator_take() {
-        let generator = WidGen::new(4, 6, None).unwrap();
-        let v: Vec<String> = generator.take(5).collect();
-        assert_eq!(v.len(), 5);
+    fn test_builder_defaults_and_seed() {
+        let built = WidGenBuilder::new().build().unwrap();
+        assert!(built.config_eq(&WidGen::default_params()));
+
+        let custom = WidGenBuilder::new()
+            .width(6)
+            .padding(0)
+            .time_unit(TimeUnit::Ms)
+            .build()
+            .unwrap();
+        assert!(custom.config_eq(&WidGen::new_with_time_unit(6, 0, None, TimeUnit::Ms).unwrap()));
+        assert!(matches!(
+            WidGenBuilder::new().width(0).build(),
+            Err(WidError::InvalidW)
+        ));
+
+        let pads = |seed| {
+            let mut generator = WidGenBuilder::new().seed(seed).build().unwrap();
+            generator
+                .next_n(5)
+                .iter()
+                .map(|id| parse_wid(id, 4, 6).unwrap().padding.unwrap())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(pads(42), pads(42));
+        assert_ne!(pads(42), pads(43));
+
+        let mut packed = WidGenBuilder::new()
+            .padding(20)
+            .padding_mode(PaddingMode::Packed)
+            .build()
+            .unwrap();
+        assert!(packed.next_n(50).iter().all(|id| validate_wid(id, 4, 20)));
     }
 
     #[test]
-    fn test_parse_valid_wids() {
-        let p = parse_wid("20260212T091530.0042Z", 4, 0).unwrap();
-        assert_eq!(p.sequence, 42);
-        assert_eq!(p.padding, None);
+    fn test_config_eq_ignores_state() {
+        let mut primary = WidGen::new(4, 6, None).unwrap();
+        let standby = WidGen::new(4, 6, None).unwrap();
+        primary.next_wid();
+        assert!(primary.config_eq(&standby));
 
-        let p2 = parse_wid("20260212T091530.0042Z-a3f91c", 4, 6).unwrap();
-        assert_eq!(p2.sequence, 42);
-        assert_eq!(p2.padding.as_deref(), Some("a3f91c"));
-        assert_eq!(p2.timestamp_sec(), p2.timestamp.timestamp());
+        assert!(!primary.config_eq(&WidGen::new(5, 6, None).unwrap()));
+        assert!(!primary.config_eq(&WidGen::new(4, 0, None).unwrap()));
+        let ms = WidGen::new_with_time_unit(4, 6, None, TimeUnit::Ms).unwrap();
+        assert!(!primary.config_eq(&ms));
+        let desc = WidGen::new_descending(4, 6, TimeUnit::Sec).unwrap();
+        assert!(!primary.config_eq(&desc));
+        let tagged = WidGen::new_with_version_tag(4, 6, TimeUnit::Sec, 1).unwrap();
+        assert!(!primary.config_eq(&tagged));
+    }
 
-        let p3 =
-            parse_wid_with_unit("20260212T091530123.0042Z-a3f91c", 4, 6, TimeUnit::Ms).unwrap();
-        assert_eq!(p3.sequence, 42);
-        assert_eq!(p3.timestamp.timestamp_subsec_millis(), 123);
+    #[test]
+    fn test_hash_for_bloom_stable_and_spread() {
+        let wid = "20260212T091530.0000Z-a3f91c";
+        let hashes = hash_for_bloom(wid, 7);
+        assert_eq!(hashes.len(), 7);
+        assert_eq!(hashes, hash_for_bloom(wid, 7));
+        assert_eq!(hash_for_bloom(wid, 3), hashes[..3]);
+        let distinct: std::collections::HashSet<_> = hashes.iter().collect();
+        assert_eq!(distinct.len(), 7);
+
+        // Consecutive IDs differ in one digit; their bits must still spread
+        // evenly over a small filter.
+        let mut buckets = [0usize; 16];
+        for seq in 0..4000 {
+            let id = format!("20260212T091530.{seq:04}Z");
+            for h in hash_for_bloom(&id, 2) {
+                buckets[(h % 16) as usize] += 1;
+            }
+        }
+        // 8000 draws over 16 buckets: expect 500 each.
+        assert!(
+            buckets.iter().all(|&n| (400..600).contains(&n)),
+            "{buckets:?}"
+        );
     }
 
     #[test]
-    fn test_parse_invalid_cases() {
+    fn test_mint_exact_round_trips() {
+        let tick = 1_770_888_930; // 2026-02-12T09:35:30Z
+        let wid = WidGen::mint_exact(tick, 42, 4, 6, TimeUnit::Sec).unwrap();
+        let parsed = parse_wid(&wid, 4, 6).unwrap();
+        assert_eq!(parsed.timestamp_sec(), tick);
+        assert_eq!(parsed.sequence, 42);
+
+        let ms = WidGen::mint_exact(tick * 1000 + 7, 9, 1, 0, TimeUnit::Ms).unwrap();
+        assert_eq!(ms, "20260212T093530007.9Z");
+
         assert!(matches!(
-            parse_wid("waldiez", 4, 6),
-            Err(WidError::InvalidFormat(_))
+            WidGen::mint_exact(tick, 10_000, 4, 6, TimeUnit::Sec),
+            Err(WidError::SequenceOutOfRange {
+                seq: 10_000,
+                max: 9999
+            })
         ));
         assert!(matches!(
-            parse_wid("20260212T091530.0000Z-ABCDEF", 4, 6),
-            Err(WidError::InvalidFormat(_))
+            WidGen::mint_exact(-1, 0, 4, 6, TimeUnit::Sec),
+            Err(WidError::InvalidTimestamp)
         ));
+    }
+
+    #[test]
+    fn test_config_covers_reports_unmatched() {
+        let ids = [
+            "20260212T091530.0000Z-a3f91c",
+            "20260212T091530.00001Z-a3f91c",
+            "20260212T091531.0042Z-000000",
+            "20260212T091531.00002Z-ffffff",
+        ];
+        let report = config_covers(&ids, 4, 6, TimeUnit::Sec);
+        assert_eq!(report.matched, 2);
+        assert_eq!(report.unmatched, vec![ids[1], ids[3]]);
+        assert_eq!(config_covers(&ids, 5, 6, TimeUnit::Sec).matched, 2);
+    }
+
+    #[test]
+    fn test_descending_ids_sort_newest_first() {
+        let mut wid_gen = WidGen::new_descending(4, 6, TimeUnit::Ms).unwrap();
+        let t = 32_503_680_000_000; // 3000-01-01, so the clock never overtakes it
+        wid_gen.restore_state(t, 9997);
+        let ids = wid_gen.next_n(4);
+        let mut sorted = ids.clone();
+        sorted.sort();
+        sorted.reverse();
+        assert_eq!(sorted, ids);
+
+        let parsed: Vec<ParsedWid> = ids
+            .iter()
+            .map(|id| parse_wid_descending(id, 4, 6, TimeUnit::Ms).unwrap())
+            .collect();
+        assert_eq!(parsed[0].timestamp.timestamp_millis(), t);
+        assert_eq!(parsed[0].sequence, 9998);
+        assert_eq!(parsed[2].timestamp.timestamp_millis(), t + 1);
+        assert_eq!(parsed[2].sequence, 0);
+
+        let token = wid_gen.reserve_token(2);
+        let reserved: Vec<String> = token.iter(4, 6, TimeUnit::Ms).collect();
+        assert!(reserved[0] < ids[3] && reserved[1] < reserved[0]);
+    }
+
+    #[test]
+    fn test_day_bounds_select_one_day() {
+        let date = NaiveDate::from_ymd_opt(2026, 2, 12).unwrap();
+        let (lo, hi) = day_bounds(date, 4, 6, TimeUnit::Sec);
+        assert_eq!(lo, "20260212T000000.0000Z-000000");
+        assert_eq!(hi, "20260212T235959.9999Z-ffffff");
+
+        let (lo_ms, hi_ms) = day_bounds(date, 2, 0, TimeUnit::Ms);
+        assert_eq!(lo_ms, "20260212T000000000.00Z");
+        assert_eq!(hi_ms, "20260212T235959999.99Z");
+
+        let in_day = |id: &str| id >= lo.as_str() && id <= hi.as_str();
+        let midnight = Utc
+            .with_ymd_and_hms(2026, 2, 12, 0, 0, 0)
+            .unwrap()
+            .timestamp();
+        for tick in [midnight, midnight + 43_200, midnight + 86_399] {
+            let id = WidGen::mint_exact(tick, 17, 4, 6, TimeUnit::Sec).unwrap();
+            assert!(in_day(&id), "{id}");
+        }
+        let next_day = WidGen::mint_exact(midnight + 86_400, 0, 4, 6, TimeUnit::Sec).unwrap();
+        assert!(!in_day(&next_day));
+        let prev_day = WidGen::mint_exact(midnight - 1, 9999, 4, 6, TimeUnit::Sec).unwrap();
+        assert!(!in_day(&prev_day));
+    }
+
+    #[test]
+    fn test_min_max_wid_at_bracket_real_ids() {
+        let ts = Utc.with_ymd_and_hms(2026, 2, 12, 9, 15, 30).unwrap() + Duration::from_millis(250);
+        assert_eq!(
+            min_wid_at(ts, 4, 6, TimeUnit::Sec),
+            "20260212T091530.0000Z-000000"
+        );
+        assert_eq!(
+            max_wid_at(ts, 4, 6, TimeUnit::Sec),
+            "20260212T091530.9999Z-ffffff"
+        );
+        assert_eq!(min_wid_at(ts, 2, 0, TimeUnit::Ms), "20260212T091530250.00Z");
+        assert_eq!(max_wid_at(ts, 2, 0, TimeUnit::Ms), "20260212T091530250.99Z");
+
+        for (z, unit) in [(6, TimeUnit::Sec), (0, TimeUnit::Sec), (4, TimeUnit::Ms)] {
+            let lo = min_wid_at(ts, 4, z, unit);
+            let hi = max_wid_at(ts, 4, z, unit);
+            assert!(validate_wid_with_unit(&lo, 4, z, unit));
+            assert!(validate_wid_with_unit(&hi, 4, z, unit));
+            let tick = unit.tick_of(ts).unwrap();
+            for seq in [1, 5000, 9998] {
+                let id = WidGen::mint_exact(tick, seq, 4, z, unit).unwrap();
+                assert!(lo < id && id < hi, "{lo} < {id} < {hi}");
+            }
+        }
+    }
+
+    #[test]
+    fn test_strip_padding() {
+        let stripped = strip_padding("20260212T091530.0042Z-a3f91c", 4, 6, TimeUnit::Sec).unwrap();
+        assert_eq!(stripped, "20260212T091530.0042Z");
+        assert!(validate_wid(&stripped, 4, 0));
+
+        let ms = strip_padding("20260212T091530123.0042Z-a3f9", 4, 4, TimeUnit::Ms).unwrap();
+        assert_eq!(ms, "20260212T091530123.0042Z");
+        assert!(validate_wid_with_unit(&ms, 4, 0, TimeUnit::Ms));
+
+        let unpadded = "20260212T091530.0042Z";
+        assert_eq!(
+            strip_padding(unpadded, 4, 0, TimeUnit::Sec).unwrap(),
+            unpadded
+        );
+
+        assert!(strip_padding("20260212T091530.0042Z-a3f9", 4, 6, TimeUnit::Sec).is_err());
+        assert!(strip_padding("not-a-wid", 4, 6, TimeUnit::Sec).is_err());
+    }
+
+    /// `TIMESTAMP|SEQ[|PAD]`.
+    struct PipeFormat;
+
+    impl WidFormat for PipeFormat {
+        fn format(&self, ts: &str, seq: &str, pad: Option<&str>) -> String {
+            match pad {
+                Some(pad) => format!("{ts}|{seq}|{pad}"),
+                None => format!("{ts}|{seq}"),
+            }
+        }
+    }
+
+    impl WidFormatParser for PipeFormat {
+        fn split<'a>(&self, wid: &'a str) -> Option<(&'a str, &'a str, Option<&'a str>)> {
+            let mut fields = wid.split('|');
+            let (ts, seq, pad) = (fields.next()?, fields.next()?, fields.next());
+            fields.next().is_none().then_some((ts, seq, pad))
+        }
+    }
+
+    #[test]
+    fn test_custom_format_round_trips_through_parser() {
+        let mut wid_gen =
+            WidGen::new_with_format(4, 6, TimeUnit::Sec, Box::new(PipeFormat)).unwrap();
+        wid_gen.clock = Box::new(ManualClock::new(1_770_887_730));
+        let ids = wid_gen.next_n(3);
+        assert!(ids[0].starts_with("20260212T091530|0000|"));
+        assert!(ids.windows(2).all(|p| p[0] < p[1]));
+        for (seq, id) in ids.iter().enumerate() {
+            assert!(!validate_wid(id, 4, 6));
+            let parsed = parse_wid_with_format(id, &PipeFormat, 4, 6, TimeUnit::Sec).unwrap();
+            assert_eq!(parsed.raw, *id);
+            assert_eq!(parsed.sequence, seq as u64);
+            assert_eq!(parsed.timestamp_sec(), 1_770_887_730);
+            assert_eq!(parsed.padding.as_deref(), id.rsplit('|').next());
+        }
+
+        let mut unpadded =
+            WidGen::new_with_format(4, 0, TimeUnit::Ms, Box::new(PipeFormat)).unwrap();
+        let id = unpadded.next_wid();
+        assert_eq!(id.matches('|').count(), 1);
+        assert!(parse_wid_with_format(&id, &PipeFormat, 4, 0, TimeUnit::Ms).is_ok());
+
         assert!(matches!(
-            parse_wid("20260212T091530.0000Z-node01", 4, 0),
+            parse_wid_with_format("a|b|c|d", &PipeFormat, 4, 6, TimeUnit::Sec),
             Err(WidError::InvalidFormat(_))
         ));
+        assert!(
+            parse_wid_with_format(
+                "20260212T091530|00x0|a3f91c",
+                &PipeFormat,
+                4,
+                6,
+                TimeUnit::Sec
+            )
+            .is_err()
+        );
+
+        let canonical = "20260212T091530.0042Z-a3f91c";
+        let parsed =
+            parse_wid_with_format(canonical, &CanonicalFormat, 4, 6, TimeUnit::Sec).unwrap();
+        assert_eq!(parsed, parse_wid(canonical, 4, 6).unwrap());
+    }
+
+    #[test]
+    fn test_custom_format_next_parsed_and_config_eq() {
+        /// Padding followed by a suffix, so it is not the last `z` characters.
+        struct Suffixed;
+        impl WidFormat for Suffixed {
+            fn format(&self, ts: &str, seq: &str, pad: Option<&str>) -> String {
+                format!("{ts}|{seq}|{}#v1", pad.unwrap_or(""))
+            }
+        }
+
+        let mut wid_gen = WidGen::new_with_format(4, 6, TimeUnit::Sec, Box::new(Suffixed)).unwrap();
+        let parsed = wid_gen.next_parsed();
+        let pad = parsed.padding.unwrap();
+        assert_eq!(pad.len(), 6);
+        assert!(
+            parsed.raw.ends_with(&format!("|{pad}#v1")),
+            "{}",
+            parsed.raw
+        );
+
+        let pipe = WidGen::new_with_format(4, 6, TimeUnit::Sec, Box::new(PipeFormat)).unwrap();
+        assert!(wid_gen.config_eq(&pipe));
+        assert!(!wid_gen.config_eq(&WidGen::new(4, 6, None).unwrap()));
+        assert!(
+            WidGen::new(4, 6, None)
+                .unwrap()
+                .config_eq(&WidGen::default_params())
+        );
+
+        fn assert_sync<T: Send + Sync>() {}
+        assert_sync::<WidGen>();
+    }
+
+    #[test]
+    fn test_age_buckets() {
+        const HOUR: i64 = 3600;
+        let now = Utc.with_ymd_and_hms(2026, 2, 12, 9, 15, 30).unwrap();
+        let edges = [1, 24, 7 * 24].map(|hours| Duration::from_secs(hours * 3600));
+        let ids: Vec<String> = [
+            -60,             // future: age zero
+            0,               // now
+            HOUR - 1,        // just under an hour
+            HOUR,            // exactly an hour: second bucket
+            5 * HOUR,        // hours
+            3 * 24 * HOUR,   // days
+            7 * 24 * HOUR,   // exactly a week: overflow
+            400 * 24 * HOUR, // over a year
+        ]
+        .iter()
+        .map(|age| WidGen::mint_exact(now.timestamp() - age, 0, 4, 6, TimeUnit::Sec).unwrap())
+        .collect();
+        let mut refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        refs.push("not-a-wid");
+        refs.push("20260212T091530.0000Z-a3f9");
+
+        assert_eq!(
+            age_buckets(&refs, now, &edges, 4, 6, TimeUnit::Sec),
+            [3, 2, 1, 2]
+        );
+        assert_eq!(age_buckets(&refs, now, &[], 4, 6, TimeUnit::Sec), [8]);
+        assert_eq!(age_buckets(&[], now, &edges, 4, 6, TimeUnit::Sec), [0; 4]);
+    }
+
+    #[test]
+    fn test_version_tag_round_trip() {
+        let mut wid_gen = WidGen::new_with_version_tag(4, 6, TimeUnit::Sec, 0xa).unwrap();
+        for id in wid_gen.next_n(20) {
+            assert!(validate_wid(&id, 4, 6));
+            assert_eq!(extract_version_tag(&id, 4, 6, TimeUnit::Sec).unwrap(), 0xa);
+        }
+        let token = wid_gen.reserve_token(3);
+        for id in token.iter(4, 6, TimeUnit::Sec) {
+            assert_eq!(extract_version_tag(&id, 4, 6, TimeUnit::Sec).unwrap(), 0xa);
+        }
+
         assert!(matches!(
-            parse_wid("20260232T091530.0000Z", 4, 0),
-            Err(WidError::InvalidTimestamp)
+            WidGen::new_with_version_tag(4, 6, TimeUnit::Sec, 16),
+            Err(WidError::InvalidVersionTag(16))
         ));
         assert!(matches!(
-            parse_wid("20260212T091530.0000Z", 0, 0),
-            Err(WidError::InvalidW)
+            WidGen::new_with_version_tag(4, 0, TimeUnit::Sec, 1),
+            Err(WidError::InvalidZ)
         ));
     }
 
     #[test]
-    fn test_state_restore_and_next_n() {
-        let mut g1 = WidGen::new(4, 0, None).unwrap();
-        let _ = g1.next_wid();
-        let _ = g1.next_wid();
-        let (last_tick, last_seq) = g1.state();
+    fn test_convert_time_unit() {
+        let (ms, lossy) = convert_time_unit(
+            "20260212T091530.0042Z-a3f91c",
+            4,
+            6,
+            TimeUnit::Sec,
+            TimeUnit::Ms,
+        )
+        .unwrap();
+        assert_eq!(ms, "20260212T091530000.0042Z-a3f91c");
+        assert!(!lossy);
 
-        let mut g2 = WidGen::new(4, 0, None).unwrap();
-        g2.restore_state(last_tick, last_seq);
-        let w = g2.next_wid();
-        let p = parse_wid(&w, 4, 0).unwrap();
-        assert_eq!(p.sequence as i64, last_seq + 1);
+        let (sec, lossy) = convert_time_unit(
+            "20260212T091530123.0042Z",
+            4,
+            0,
+            TimeUnit::Ms,
+            TimeUnit::Sec,
+        )
+        .unwrap();
+        assert_eq!(sec, "20260212T091530.0042Z");
+        assert!(lossy);
 
-        let v = g2.next_n(3);
-        assert_eq!(v.len(), 3);
+        assert!(convert_time_unit(&ms, 4, 6, TimeUnit::Sec, TimeUnit::Ms).is_err());
     }
 
     #[test]
-    fn test_default_params_and_scope_rejected() {
-        let mut g = WidGen::default_params();
-        let w = g.next_wid();
-        assert!(validate_wid(&w, 4, 6));
-        assert!(matches!(
-            WidGen::new(4, 6, Some("acme".to_string())),
-            Err(WidError::InvalidScope)
-        ));
+    fn test_large_w_sequence_round_trips() {
+        let mut wid_gen = WidGen::new(12, 0, None).unwrap();
+        wid_gen.restore_state(32_503_680_000, 5_000_000_000);
+        let wid = wid_gen.next_wid();
+        assert!(wid.ends_with(".005000000001Z"));
+        assert_eq!(parse_wid(&wid, 12, 0).unwrap().sequence, 5_000_000_001);
     }
 
     #[test]
-    fn test_ms_generator_shape() {
-        let mut g = WidGen::new_with_time_unit(4, 0, None, TimeUnit::Ms).unwrap();
-        let w = g.next_wid();
-        assert!(validate_wid_with_unit(&w, 4, 0, TimeUnit::Ms));
+    fn test_custom_padding_provider() {
+        struct Pattern;
+        impl PaddingProvider for Pattern {
+            fn fill(&mut self, out: &mut String, z: usize, alphabet: &[u8]) {
+                out.extend((0..z).map(|i| alphabet[10 + i % 3] as char));
+            }
+        }
+
+        let mut wid_gen =
+            WidGen::new_with_padding_provider(4, 6, TimeUnit::Sec, Box::new(Pattern)).unwrap();
+        for id in wid_gen.next_n(3) {
+            assert!(id.ends_with("-abcabc"), "{id}");
+            assert!(validate_wid(&id, 4, 6));
+        }
+        let token = wid_gen.reserve_token(3);
+        for id in wid_gen.expand_reservation(&token).unwrap() {
+            assert!(id.ends_with("-abcabc"), "{id}");
+        }
+
+        fn assert_sync<T: Send + Sync + ?Sized>() {}
+        assert_sync::<dyn PaddingProvider>();
+        assert_sync::<dyn Clock>();
+
+        let mut out = String::new();
+        HexPadding.fill(&mut out, 8, HEX_ALPHABET);
+        assert_eq!(out.len(), 8);
+        assert!(out.bytes().all(|b| HEX_ALPHABET.contains(&b)));
+    }
+
+    #[test]
+    fn test_same_generator_plausible() {
+        let a = "20260212T091530.0003Z-a3f91c";
+        let later_seq = "20260212T091530.0004Z-000000";
+        let later_tick = "20260212T091531.0000Z-ffffff";
+        let unit = TimeUnit::Sec;
+        assert!(same_generator_plausible(a, later_seq, 4, 6, unit).unwrap());
+        assert!(same_generator_plausible(a, later_tick, 4, 6, unit).unwrap());
+        assert!(!same_generator_plausible(later_seq, a, 4, 6, unit).unwrap());
+        assert!(!same_generator_plausible(a, a, 4, 6, unit).unwrap());
+        assert!(same_generator_plausible(a, "20260212T091531.00000Z-ffffff", 4, 6, unit).is_err());
+    }
+
+    #[test]
+    fn test_shared_seeded_padding_is_reproducible() {
+        let paddings = |seed: u64| -> Vec<String> {
+            let shared = SharedWidGen::new_seeded(seed);
+            (0..20)
+                .map(|_| shared.next_wid().rsplit('-').next().unwrap().to_string())
+                .collect()
+        };
+        assert_eq!(paddings(42), paddings(42));
+        assert_ne!(paddings(42), paddings(43));
+
+        // Threads run one after another get the same seeds on every run.
+        let per_thread = |seed: u64| -> Vec<String> {
+            let shared = SharedWidGen::new_seeded_with(WidGen::new(4, 8, None).unwrap(), seed);
+            (0..3)
+                .flat_map(|_| {
+                    std::thread::scope(|s| {
+                        s.spawn(|| (0..5).map(|_| shared.next_wid()).collect::<Vec<_>>())
+                            .join()
+                            .unwrap()
+                    })
+                })
+                .map(|id| id.rsplit('-').next().unwrap().to_string())
+                .collect()
+        };
+        let run = per_thread(7);
+        assert_eq!(run, per_thread(7));
+        assert_ne!(run[..5], run[5..10]);
+
+        let shared = SharedWidGen::new(WidGen::default_params());
+        let ids: Vec<String> = std::thread::scope(|s| {
+            let handles: Vec<_> = (0..4)
+                .map(|_| s.spawn(|| (0..50).map(|_| shared.next_wid()).collect::<Vec<_>>()))
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|h| h.join().unwrap())
+                .collect()
+        });
+        let unique: std::collections::HashSet<_> = ids.iter().collect();
+        assert_eq!(unique.len(), 200);
+
+        // Pool threads' RNGs live in the instance, not in the threads.
+        let seeded = SharedWidGen::new_seeded(1);
+        std::thread::scope(|s| {
+            for _ in 0..3 {
+                s.spawn(|| seeded.next_wid());
+            }
+        });
+        let state = seeded.state.lock().unwrap();
+        assert_eq!(state.thread_rngs.len(), 3);
+    }
+
+    #[test]
+    fn test_reservations_are_disjoint() {
+        let mut g = WidGen::new(2, 4, None).unwrap();
+        let a = g.reserve_token(150);
+        let b = g.reserve_token(30);
+        assert_eq!(a.count, 150);
+
+        let ids_a: Vec<String> = a.iter(2, 4, TimeUnit::Sec).collect();
+        let ids_b: Vec<String> = b.iter(2, 4, TimeUnit::Sec).collect();
+        assert_eq!(ids_a.len(), 150);
+        assert_eq!(ids_b.len(), 30);
+        for id in [&ids_a[0], &ids_a[99], &ids_a[100], &ids_b[29]] {
+            assert!(validate_wid(id, 2, 4));
+        }
+
+        // Compare without padding: slots must not repeat across reservations.
+        let slot = |id: &String| id[..id.len() - 5].to_string();
+        assert!(ids_a.windows(2).all(|p| slot(&p[0]) < slot(&p[1])));
+        assert!(slot(ids_a.last().unwrap()) < slot(&ids_b[0]));
+        assert!(slot(ids_b.last().unwrap()) < slot(&g.next_wid()));
+
+        let json = serde_json::to_string(&b).unwrap();
+        assert_eq!(serde_json::from_str::<Reservation>(&json).unwrap(), b);
+    }
+
+    #[test]
+    fn test_reservation_rejects_out_of_range_counts() {
+        let mut g = WidGen::new(4, 0, None).unwrap();
+        let before = g.state();
+        assert!(matches!(
+            g.try_reserve_token(usize::MAX),
+            Err(WidError::ReservationOutOfRange(usize::MAX))
+        ));
+        // Fits in i64 but runs past year 9999.
+        assert!(g.try_reserve_token(i64::MAX as usize).is_err());
+        assert_eq!(g.state(), before);
+
+        let token = g.try_reserve_token(3).unwrap();
+        assert_eq!(token.iter(4, 0, TimeUnit::Sec).count(), 3);
+
+        let forged = Reservation {
+            count: usize::MAX,
+            ..token
+        };
+        assert!(matches!(
+            forged.try_iter(4, 0, TimeUnit::Sec),
+            Err(WidError::ReservationOutOfRange(_))
+        ));
+        assert!(matches!(
+            token.try_iter(0, 0, TimeUnit::Sec),
+            Err(WidError::InvalidW)
+        ));
     }
 }
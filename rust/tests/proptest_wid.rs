@@ -0,0 +1,99 @@
+//! Property tests for generator monotonicity and uniqueness across random
+//! `W`, `Z` and time units.
+//!
+//! Every parse with non-default parameters compiles its own regex, which is
+//! slow in debug builds, so case counts and batch sizes are kept small; small
+//! `W` values still cross several sequence rollovers per case.
+
+use std::collections::HashSet;
+
+use proptest::prelude::*;
+use wid::{
+    HLCWidGen, TimeUnit, WidGen, parse_batch, parse_hlc_wid_with_unit, parse_wid_with_unit,
+    validate_hlc_wid_with_unit, validate_wid_with_unit,
+};
+
+fn time_unit() -> impl Strategy<Value = TimeUnit> {
+    prop_oneof![Just(TimeUnit::Sec), Just(TimeUnit::Ms), Just(TimeUnit::Ns)]
+}
+
+/// `(w, z, time_unit, n_ids)`; small `w` forces sequence rollover.
+fn generator_params() -> impl Strategy<Value = (usize, usize, TimeUnit, usize)> {
+    (1usize..=8, 0usize..=12, time_unit(), 1usize..=30)
+}
+
+/// A tick far enough ahead that the clock never catches up during a test:
+/// 3000-01-01, or 2200-01-01 for `ns`, whose range ends in 2262.
+fn far_future_tick(time_unit: TimeUnit) -> i64 {
+    let secs = 32_503_680_000;
+    match time_unit {
+        TimeUnit::Sec => secs,
+        TimeUnit::Ms => secs * 1_000,
+        TimeUnit::Ns => 7_258_118_400 * 1_000_000_000,
+    }
+}
+
+proptest! {
+    #![proptest_config(ProptestConfig::with_cases(32))]
+
+    #[test]
+    fn wid_gen_is_monotonic_and_unique((w, z, time_unit, n) in generator_params()) {
+        let mut generator = WidGen::new_with_time_unit(w, z, None, time_unit).unwrap();
+        let ids = generator.next_n(n);
+
+        let refs: Vec<&str> = ids.iter().map(String::as_str).collect();
+        let (parsed, failures) = parse_batch(&refs, w, z, time_unit);
+        prop_assert!(failures.is_empty(), "unparseable: {failures:?}");
+        for id in &ids {
+            prop_assert!(validate_wid_with_unit(id, w, z, time_unit), "invalid {id}");
+        }
+        for pair in parsed.windows(2) {
+            let (prev, next) = (&pair[0], &pair[1]);
+            prop_assert!(
+                (next.timestamp, next.sequence) > (prev.timestamp, prev.sequence),
+                "{} does not follow {}", next.raw, prev.raw
+            );
+        }
+        let unique: HashSet<&String> = ids.iter().collect();
+        prop_assert_eq!(unique.len(), n);
+    }
+
+    #[test]
+    fn wid_gen_rolls_over_at_max_seq(w in 1usize..=8, z in 0usize..=12, time_unit in time_unit()) {
+        let mut generator = WidGen::new_with_time_unit(w, z, None, time_unit).unwrap();
+        let tick = far_future_tick(time_unit);
+        let max_seq = 10_i64.pow(w as u32) - 1;
+        generator.restore_state(tick, max_seq - 1);
+
+        let last = parse_wid_with_unit(&generator.next_wid(), w, z, time_unit).unwrap();
+        prop_assert_eq!(last.sequence, max_seq as u64);
+        let next = parse_wid_with_unit(&generator.next_wid(), w, z, time_unit).unwrap();
+        prop_assert_eq!(next.sequence, 0);
+        prop_assert!(next.timestamp > last.timestamp);
+        prop_assert_eq!(generator.state(), (tick + 1, 0));
+    }
+
+    #[test]
+    fn hlc_gen_is_monotonic_and_unique(
+        (w, z, time_unit, n) in generator_params(),
+        node in "[A-Za-z0-9]{1,16}",
+    ) {
+        let mut generator = HLCWidGen::new_with_time_unit(node.clone(), w, z, time_unit).unwrap();
+        let ids: Vec<String> = (0..n).map(|_| generator.next_hlc_wid()).collect();
+        let mut seen = HashSet::new();
+        let mut last = None;
+        for id in ids {
+            prop_assert!(validate_hlc_wid_with_unit(&id, w, z, time_unit), "invalid {id}");
+            let parsed = parse_hlc_wid_with_unit(&id, w, z, time_unit);
+            prop_assert!(parsed.is_ok(), "invalid {id}");
+            let parsed = parsed.unwrap();
+            prop_assert_eq!(&parsed.node, &node);
+            let slot = (parsed.timestamp, parsed.logical_counter);
+            if let Some(prev) = last {
+                prop_assert!(slot > prev, "{id} does not follow {prev:?}");
+            }
+            last = Some(slot);
+            prop_assert!(seen.insert(id), "duplicate ID");
+        }
+    }
+}